@@ -61,6 +61,7 @@ type Session = {
     title: string;
     raw: string;
     entities: SessionEntity[];
+    purged: boolean;
 };
 
 function iconFor(type: string) {
@@ -374,7 +375,11 @@ export default function Home() {
 
                                 <details className="text-sm text-gray-300">
                                     <summary className="cursor-pointer mb-2">Rohtranskript</summary>
-                                    <pre className="whitespace-pre-wrap">{currentSession.raw}</pre>
+                                    {currentSession.purged ? (
+                                        <p className="text-gray-500">Transkript gemäß Aufbewahrungsrichtlinie gelöscht</p>
+                                    ) : (
+                                        <pre className="whitespace-pre-wrap">{currentSession.raw}</pre>
+                                    )}
                                 </details>
                             </>
                         )}
@@ -1,12 +1,30 @@
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 // src/agents/agent.rs
+use std::path::{Path, PathBuf};
 use crate::pipeline::context::RecordContext;
 
+/// Dependency declarations mirror `PipelineStep`, see there.
 #[async_trait]
 pub trait Agent: Send + Sync {
     fn name(&self) -> &'static str;
     async fn run_document(&self, ctx: &RecordContext) -> anyhow::Result<()>;
+
+    fn depends_on(&self) -> Vec<&'static str> {
+        Vec::new()
+    }
+
+    fn inputs(&self, _ctx: &RecordContext, _record_dir: &Path) -> Vec<PathBuf> {
+        Vec::new()
+    }
+
+    fn outputs(&self, _record_dir: &Path) -> Vec<PathBuf> {
+        Vec::new()
+    }
+
+    fn critical(&self) -> bool {
+        false
+    }
 }
 
 pub fn strip_json_fences(s: &str) -> &str {
@@ -20,3 +38,16 @@ pub fn strip_json_fences(s: &str) -> &str {
         s
     }
 }
+
+/// Removes what `agent` wrote to the document's evidence.json in earlier runs.
+/// Used when a run finds no relations, so relations found before don't linger.
+pub async fn clear_agent_evidence(record_dir: &Path, agent: &str) -> anyhow::Result<()> {
+    let evidence_path = record_dir.join("evidence.json");
+    if !evidence_path.exists() {
+        return Ok(());
+    }
+
+    let mut store = crate::store::evidence::EvidenceStore::lock_and_load(record_dir).await?;
+    store.remove_agent(agent);
+    store.save().await
+}
@@ -4,49 +4,22 @@ use crate::store::evidence::{EvidenceRecord, EvidenceStore};
 
 use anyhow::{Context, Result};
 use async_trait::async_trait;
-use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::{Value};
+use std::path::{Path, PathBuf};
 use tokio::fs;
-use crate::agents::agent::{strip_json_fences, Agent};
+use uuid::Uuid;
+use crate::agents::agent::{clear_agent_evidence, strip_json_fences, Agent};
+use crate::openai::chat::{chat_completion, ChatRequest};
+use crate::prompts::builtin::CONTEXT_RELATION;
+use crate::prompts::registry::resolve_prompt;
+use crate::prompts::template::PromptTemplate;
 use crate::processing::utils::load_document_id;
 
-const PROMPT_TEMPLATE: &str = r#"
-You are analyzing a single document.
-
-The document is a source, not the truth.
-Do not infer intent, roles, or importance.
-Do not generalize.
-Only extract relationships that are explicitly stated in the text.
-
-Document text:
----
-{DOCUMENT_TEXT}
----
-
-Extracted entities (with type):
-{ENTITIES_JSON}
-
-Task:
-- Identify explicit relationships between any two entities mentioned in the document
-- Only if the relationship is clearly stated in the text
-- If no explicit relationship is stated, return an empty list
-
-Return JSON only (no markdown):
-
-[
-  {{
-    "from_type": "<entity_type>",
-    "from": "<entity_text>",
-    "to_type": "<entity_type>",
-    "to": "<entity_text>",
-    "confidence": 0.0-1.0
-  }}
-]
-"#;
-
 pub struct ContextRelationAgent {
     pub openai_api_key: String,
+    /// Template to use instead of the active one (A/B runs).
+    pub prompt: Option<PromptTemplate>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -65,36 +38,24 @@ struct RelationCandidate {
     confidence: f32,
 }
 
-#[derive(Debug, Serialize)]
-struct ChatRequest {
-    model: String,
-    messages: Vec<crate::agents::context_relation::ChatMessage>,
-    temperature: f32,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct ChatMessage {
-    role: String,
-    content: String,
-}
-
-#[derive(Debug, Deserialize)]
-struct ChatResponse {
-    choices: Vec<ChatChoice>,
-}
-
-#[derive(Debug, Deserialize)]
-struct ChatChoice {
-    message: crate::agents::context_relation::ChatMessage,
-}
-
-
 #[async_trait]
 impl Agent for ContextRelationAgent {
     fn name(&self) -> &'static str {
         "CONTEXT_RELATION_AGENT"
     }
 
+    fn depends_on(&self) -> Vec<&'static str> {
+        vec!["entity-extraction"]
+    }
+
+    fn inputs(&self, _ctx: &RecordContext, record_dir: &Path) -> Vec<PathBuf> {
+        vec![record_dir.join("text.txt"), record_dir.join("entities.json")]
+    }
+
+    fn outputs(&self, record_dir: &Path) -> Vec<PathBuf> {
+        vec![record_dir.join("evidence.json")]
+    }
+
     async fn run_document(&self, ctx: &RecordContext) -> Result<()> {
         println!("▶ ContextRelationAgent: run_document");
 
@@ -124,48 +85,50 @@ impl Agent for ContextRelationAgent {
 
         if entities.len() < 2 {
             println!("▶ ContextRelationAgent: not enough entities");
-            return Ok(());
+            return clear_agent_evidence(&record_dir, self.name()).await;
         }
 
-        let prompt = PROMPT_TEMPLATE
-            .replace("{DOCUMENT_TEXT}", &document_text)
-            .replace("{ENTITIES_JSON}", &serde_json::to_string_pretty(&entities)?);
+        let template = resolve_prompt(ctx, CONTEXT_RELATION, &self.prompt).await?;
+        println!("▶ ContextRelationAgent: prompt {}", template.id());
+        ctx.record_prompt(&template.id());
+
+        let entities_json = serde_json::to_string_pretty(&entities)?;
+        let messages = template.render(&[
+            ("DOCUMENT_TEXT", &document_text),
+            ("ENTITIES_JSON", &entities_json),
+        ]);
 
         let request = ChatRequest {
             model: "gpt-4o-mini".into(),
-            messages: vec![ChatMessage {
-                role: "user".into(),
-                content: prompt,
-            }],
+            messages,
             temperature: 0.0,
         };
 
-        let client = Client::new();
-        let response = client
-            .post("https://api.openai.com/v1/chat/completions")
-            .bearer_auth(&self.openai_api_key)
-            .json(&request)
-            .send()
-            .await?;
-
-        let response: ChatResponse = response.json().await?;
-        let raw = response.choices.get(0).map(|c| c.message.content.clone()).unwrap_or("[]".into());
+        let raw = chat_completion(ctx, self.name(), &self.openai_api_key, &request).await?;
         let cleaned = strip_json_fences(&raw);
 
         let candidates: Vec<RelationCandidate> =
             serde_json::from_str(cleaned).unwrap_or_default();
+        ctx.record_count("candidates", candidates.len());
 
         if candidates.is_empty() {
             println!("▶ ContextRelationAgent: no relations found");
-            return Ok(());
+            return clear_agent_evidence(&record_dir, self.name()).await;
         }
 
-        let mut store = EvidenceStore::load_or_create(&record_dir).await?;
+        // the other relation agent may write evidence.json at the same time
+        let mut store = EvidenceStore::lock_and_load(&record_dir).await?;
+        ctx.ensure_not_cancelled()?;
+        // this run replaces the previous one instead of adding to it
+        store.remove_agent(self.name());
+        let run_id = Uuid::new_v4().to_string();
+        let mut written = 0;
 
         for c in candidates {
             if c.confidence <= 0.0 {
                 continue;
             }
+            written += 1;
 
             let key = format!(
                 "{}:{}|associated_with|{}:{}",
@@ -175,23 +138,26 @@ impl Agent for ContextRelationAgent {
                 c.to.to_lowercase()
             );
 
-            let record = EvidenceRecord::new(
+            let mut record = EvidenceRecord::new(
                 key,
-                c.from_type.clone(),
+                c.from_type.clone().into(),
                 c.from.clone(),
                 "associated_with".into(),
                 c.to.clone(),
             );
+            record.prompt_versions.push(template.id());
 
             store.add_or_update(
                 record,
                 &document_id,
                 c.confidence,
                 self.name(),
+                &run_id,
             );
         }
 
         store.save().await?;
+        ctx.record_count("evidence_written", written);
         println!("▶ ContextRelationAgent finished");
 
         Ok(())
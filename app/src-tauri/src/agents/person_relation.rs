@@ -4,40 +4,24 @@ use crate::store::evidence::{EvidenceRecord, EvidenceStore};
 
 use anyhow::{Context, Result};
 use async_trait::async_trait;
-use reqwest::Client;
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 use serde_json::{Value};
+use std::path::{Path, PathBuf};
 use tokio::fs;
-use crate::agents::agent::{strip_json_fences, Agent};
+use uuid::Uuid;
+use crate::agents::agent::{clear_agent_evidence, strip_json_fences, Agent};
+use crate::openai::chat::{chat_completion, ChatRequest};
+use crate::prompts::builtin::PERSON_RELATION;
+use crate::prompts::registry::resolve_prompt;
+use crate::prompts::template::PromptTemplate;
 use crate::processing::utils::load_document_id;
 
 pub struct PersonRelationAgent {
     pub openai_api_key: String,
+    /// Template to use instead of the active one (A/B runs).
+    pub prompt: Option<PromptTemplate>,
 }
 
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ChatMessage {
-    role: String,
-    content: String,
-}
-
-#[derive(Debug, Serialize)]
-pub struct ChatRequest {
-    model: String,
-    messages: Vec<ChatMessage>,
-    temperature: f32,
-}
-
-#[derive(Debug, Deserialize)]
-pub struct ChatResponse {
-    choices: Vec<ChatChoice>,
-}
-
-#[derive(Debug, Deserialize)]
-pub struct ChatChoice {
-    message: ChatMessage,
-}
 #[derive(Debug, Deserialize)]
 struct Entity {
     #[serde(rename = "type")]
@@ -59,6 +43,18 @@ impl Agent for PersonRelationAgent {
         "PERSON_RELATION_AGENT"
     }
 
+    fn depends_on(&self) -> Vec<&'static str> {
+        vec!["entity-extraction"]
+    }
+
+    fn inputs(&self, _ctx: &RecordContext, record_dir: &Path) -> Vec<PathBuf> {
+        vec![record_dir.join("text.txt"), record_dir.join("entities.json")]
+    }
+
+    fn outputs(&self, record_dir: &Path) -> Vec<PathBuf> {
+        vec![record_dir.join("evidence.json")]
+    }
+
     async fn run_document(&self, ctx: &RecordContext) -> Result<()> {
         println!("▶ PersonRelationAgent: run_document");
 
@@ -121,7 +117,7 @@ impl Agent for PersonRelationAgent {
         println!("▶ PersonRelationAgent: persons={}, orgs={}", persons.len(), orgs.len());
         if persons.is_empty() || orgs.is_empty() {
             println!("▶ PersonRelationAgent: no persons or organizations");
-            return Ok(());
+            return clear_agent_evidence(&record_dir, self.name()).await;
         }
 
         println!("▶ PersonRelationAgent: reading {}", text_path.display());
@@ -131,49 +127,22 @@ impl Agent for PersonRelationAgent {
         let preview: String = document_text.chars().take(300).collect();
         println!("▶ PersonRelationAgent: text preview: {}", preview.replace('\n', " "));
 
+        let template = resolve_prompt(ctx, PERSON_RELATION, &self.prompt).await?;
+        println!("▶ PersonRelationAgent: prompt {}", template.id());
+        ctx.record_prompt(&template.id());
+
         // Wichtig: Text in den Prompt
-        let prompt = format!(
-            r#"You are analyzing a single document.
-
-The document is a source, not the truth.
-Do not assume roles.
-Do not decide facts.
-Only propose relations that are explicitly stated in the document text.
-If there is no explicit statement linking a person to an organization, return an empty list.
-
-Document text:
----
-{}
----
-
-Persons found (extracted):
-{}
-
-Organizations found (extracted):
-{}
-
-Task:
-- Identify which persons are explicitly related to which organizations in THIS document
-- Do not invent relations
-- If unsure, return an empty list
-
-Return JSON only (no markdown):
-[
-  {{
-    "person": "<name>",
-    "organization": "<name>",
-    "confidence": 0.0-1.0
-  }}
-]
-"#,
-            document_text,
-            serde_json::to_string(&persons)?,
-            serde_json::to_string(&orgs)?,
-        );
+        let persons_json = serde_json::to_string(&persons)?;
+        let orgs_json = serde_json::to_string(&orgs)?;
+        let messages = template.render(&[
+            ("DOCUMENT_TEXT", &document_text),
+            ("PERSONS_JSON", &persons_json),
+            ("ORGANIZATIONS_JSON", &orgs_json),
+        ]);
 
         let request = ChatRequest {
             model: "gpt-4.1-mini".into(),
-            messages: vec![ChatMessage { role: "user".into(), content: prompt }],
+            messages,
             temperature: 0.0,
         };
 
@@ -183,31 +152,8 @@ Return JSON only (no markdown):
             orgs.len()
         );
 
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
-            .user_agent("via/0.1 person-relation")
-            .build()
-            .context("failed to build reqwest client")?;
-
-        let response = client
-            .post("https://api.openai.com/v1/chat/completions")
-            .bearer_auth(&self.openai_api_key)
-            .json(&request)
-            .send()
-            .await
-            .context("failed to send OpenAI request")?;
-
-        if !response.status().is_success() {
-            let body = response.text().await.unwrap_or_default();
-            anyhow::bail!("OpenAI error: {}", body);
-        }
-
-        let response: ChatResponse = response.json().await.context("invalid OpenAI response")?;
-        let raw_content = response
-            .choices
-            .get(0)
-            .map(|c| c.message.content.clone())
-            .unwrap_or_else(|| "[]".into());
+        let raw_content =
+            chat_completion(ctx, self.name(), &self.openai_api_key, &request).await?;
 
         let cleaned = strip_json_fences(&raw_content);
 
@@ -222,17 +168,25 @@ Return JSON only (no markdown):
             }
         };
 
+        ctx.record_count("candidates", candidates.len());
         if candidates.is_empty() {
             println!("▶ PersonRelationAgent: no relation candidates");
-            return Ok(());
+            return clear_agent_evidence(&record_dir, self.name()).await;
         }
 
-        let mut store = EvidenceStore::load_or_create(&record_dir).await?;
+        // the other relation agent may write evidence.json at the same time
+        let mut store = EvidenceStore::lock_and_load(&record_dir).await?;
+        ctx.ensure_not_cancelled()?;
+        // this run replaces the previous one instead of adding to it
+        store.remove_agent(self.name());
+        let run_id = Uuid::new_v4().to_string();
+        let mut written = 0;
 
         for c in candidates {
             if c.confidence <= 0.0 {
                 continue;
             }
+            written += 1;
 
             let key = format!(
                 "person:{}|associated_with|{}",
@@ -249,11 +203,13 @@ Return JSON only (no markdown):
             );
 
             record.extra.insert("role_candidate".into(), Value::Bool(false));
+            record.prompt_versions.push(template.id());
 
-            store.add_or_update(record, &document_id, c.confidence, self.name());
+            store.add_or_update(record, &document_id, c.confidence, self.name(), &run_id);
         }
 
         store.save().await?;
+        ctx.record_count("evidence_written", written);
         println!("▶ PersonRelationAgent finished");
 
         Ok(())
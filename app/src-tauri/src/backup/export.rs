@@ -0,0 +1,112 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs::File;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+use walkdir::WalkDir;
+
+use super::{include_in_backup, relative, BackupFile, BackupManifest, FORMAT, MANIFEST};
+use crate::store::atomic::revision;
+use crate::store::lock::lock_file;
+use crate::store::schema::{self, BACKUP};
+
+/// Writes the data root (sessions, transcripts, processing files, stores, settings, prompts, snapshots)
+/// into one .tar.gz at `target`. Audio is only included with `include_audio`.
+pub async fn export_backup(data_root: &Path, target: &Path, include_audio: bool) -> Result<BackupManifest> {
+    anyhow::ensure!(
+        !target.starts_with(data_root),
+        "the backup cannot be written into the data directory"
+    );
+
+    println!("▶ backup: exporting {} → {}", data_root.display(), target.display());
+
+    // the global stores do not change while they are read, evidence before knowledge
+    let _signals = lock_file(&data_root.join("signals.json")).await;
+    let _evidence = lock_file(&data_root.join("evidence.json")).await;
+    let _knowledge = lock_file(&data_root.join("knowledge.json")).await;
+
+    let root = data_root.to_path_buf();
+    let target = target.to_path_buf();
+    let manifest =
+        tokio::task::spawn_blocking(move || write_archive(&root, &target, include_audio)).await??;
+
+    println!("▶ backup: {} files written", manifest.files.len());
+    Ok(manifest)
+}
+
+/// Files first, manifest.json last. Written next to `target` and renamed when complete.
+fn write_archive(data_root: &Path, target: &Path, include_audio: bool) -> Result<BackupManifest> {
+    let mut tmp_name = target
+        .file_name()
+        .context("backup path has no file name")?
+        .to_os_string();
+    tmp_name.push(".tmp");
+    let tmp = target.with_file_name(tmp_name);
+
+    let file = File::create(&tmp).with_context(|| format!("failed to create {}", tmp.display()))?;
+    let mut archive = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+
+    let mut files = Vec::new();
+    for entry in WalkDir::new(data_root).sort_by_file_name() {
+        let entry = entry?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let Some(path) = relative(data_root, entry.path()) else {
+            continue;
+        };
+        if !include_in_backup(&path, include_audio) {
+            continue;
+        }
+
+        let contents = std::fs::read(entry.path())
+            .with_context(|| format!("failed to read {}", entry.path().display()))?;
+        let mtime = entry
+            .metadata()?
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        append(&mut archive, &format!("data/{}", path), &contents, mtime)?;
+        files.push(BackupFile {
+            path,
+            size: contents.len() as u64,
+            sha256: revision(&contents),
+        });
+    }
+
+    let now = Utc::now();
+    let manifest = BackupManifest {
+        format: FORMAT.into(),
+        app_version: env!("CARGO_PKG_VERSION").into(),
+        created_at: now.to_rfc3339(),
+        data_root: data_root.to_string_lossy().to_string(),
+        include_audio,
+        files,
+    };
+    let json = schema::to_json(&BACKUP, &manifest)?;
+    append(&mut archive, MANIFEST, json.as_bytes(), now.timestamp().max(0) as u64)?;
+
+    archive
+        .into_inner()?
+        .finish()?
+        .sync_all()
+        .with_context(|| format!("failed to write {}", tmp.display()))?;
+    std::fs::rename(&tmp, target)
+        .with_context(|| format!("failed to move backup to {}", target.display()))?;
+
+    Ok(manifest)
+}
+
+fn append(archive: &mut tar::Builder<GzEncoder<File>>, path: &str, contents: &[u8], mtime: u64) -> Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(contents.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(mtime);
+    archive
+        .append_data(&mut header, path, contents)
+        .with_context(|| format!("failed to add {} to the backup", path))
+}
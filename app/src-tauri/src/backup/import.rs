@@ -0,0 +1,362 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashSet};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use uuid::Uuid;
+use walkdir::WalkDir;
+
+use super::{is_safe, relative, BackupManifest, FORMAT, MANIFEST};
+use crate::jobs::queue::JobQueue;
+use crate::paths::next_free_stem;
+use crate::processing::state_global::rebuild_global_state;
+use crate::processing::utils::load_document_id;
+use crate::storage::migrate::migrate_json_to_sqlite;
+use crate::storage::sqlite::SqliteStorage;
+use crate::store::atomic::{revision, write_atomic};
+use crate::store::evidence::EvidenceStore;
+use crate::store::knowledge::KnowledgeStore;
+use crate::store::lock::lock_file;
+use crate::store::schema::{self, BACKUP, PROCESSING};
+use crate::store::settings::Settings;
+use crate::store::snapshot::create_snapshot;
+
+/// replace: the current data directory is set aside as data-before-import-{time}, the backup takes its place.
+/// Refused while jobs are queued or running.
+/// merge: sessions, prompts and snapshots missing here are added, local data wins on conflicts.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportMode {
+    Merge,
+    Replace,
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct ImportSummary {
+    pub files: usize,
+    pub sessions_added: usize,
+    pub sessions_skipped: usize,
+    pub knowledge_added: usize,
+    pub confirmations_added: usize,
+    /// Where the replaced data directory was moved to.
+    pub previous_data: Option<PathBuf>,
+}
+
+/// Validates the archive against its manifest before anything in the data root is touched.
+/// The archive is unpacked next to the data root, so the final move is a rename.
+pub async fn import_backup(data_root: &Path, archive: &Path, mode: ImportMode) -> Result<ImportSummary> {
+    println!("▶ backup: importing {} ({:?})", archive.display(), mode);
+
+    let staging = data_root.with_file_name(format!("import-{}", Uuid::new_v4()));
+    let result = import_staged(data_root, archive, &staging, mode).await;
+
+    if staging.exists() {
+        if let Err(err) = fs::remove_dir_all(&staging).await {
+            println!("⚠ backup: cannot remove {} → {}", staging.display(), err);
+        }
+    }
+    result
+}
+
+async fn import_staged(
+    data_root: &Path,
+    archive: &Path,
+    staging: &Path,
+    mode: ImportMode,
+) -> Result<ImportSummary> {
+    let (source, target) = (archive.to_path_buf(), staging.to_path_buf());
+    tokio::task::spawn_blocking(move || unpack(&source, &target)).await??;
+
+    let manifest = verify(staging).await?;
+    let staged_root = staging.join("data");
+    fs::create_dir_all(&staged_root).await?;
+    relocate(&staged_root, Path::new(&manifest.data_root), data_root).await?;
+
+    let mut summary = match mode {
+        ImportMode::Replace => ImportSummary {
+            // jobs write into the data root, it must not move away underneath them
+            previous_data: JobQueue::while_idle(data_root, replace(data_root, &staged_root)).await?,
+            ..Default::default()
+        },
+        ImportMode::Merge => merge(data_root, &staged_root, &manifest).await?,
+    };
+    summary.files = manifest.files.len();
+
+    // via.db is not part of the backup
+    if Settings::load_or_create(data_root).await?.storage.backend == "sqlite" {
+        migrate_json_to_sqlite(data_root).await?;
+    }
+
+    println!("▶ backup: imported {} files", summary.files);
+    Ok(summary)
+}
+
+fn unpack(archive: &Path, staging: &Path) -> Result<()> {
+    std::fs::create_dir_all(staging)?;
+    let file = File::open(archive).with_context(|| format!("failed to open {}", archive.display()))?;
+    let mut archive = tar::Archive::new(GzDecoder::new(file));
+
+    for entry in archive.entries().context("not a backup archive")? {
+        let mut entry = entry?;
+        let path = entry.path()?.to_string_lossy().to_string();
+        if !entry.header().entry_type().is_file() {
+            anyhow::bail!("backup contains {}, which is not a file", path);
+        }
+        if !entry.unpack_in(staging)? {
+            anyhow::bail!("backup contains the unsafe path {}", path);
+        }
+    }
+    Ok(())
+}
+
+/// Every file must be listed with its checksum, and nothing unlisted may be in the archive.
+async fn verify(staging: &Path) -> Result<BackupManifest> {
+    let path = staging.join(MANIFEST);
+    anyhow::ensure!(path.exists(), "the archive has no {}", MANIFEST);
+
+    let loaded = schema::load(&path, &BACKUP).await?;
+    let manifest: BackupManifest =
+        serde_json::from_value(loaded.value).with_context(|| format!("invalid {}", MANIFEST))?;
+    anyhow::ensure!(manifest.format == FORMAT, "the archive is not a VIA backup");
+
+    let staged_root = staging.join("data");
+    let mut listed = HashSet::new();
+    for file in &manifest.files {
+        anyhow::ensure!(is_safe(&file.path), "backup lists the unsafe path {}", file.path);
+
+        let contents = fs::read(staged_root.join(&file.path))
+            .await
+            .with_context(|| format!("{} is missing in the backup", file.path))?;
+        if contents.len() as u64 != file.size || revision(&contents) != file.sha256 {
+            anyhow::bail!("{} is damaged, checksum mismatch", file.path);
+        }
+        listed.insert(file.path.as_str());
+    }
+
+    for entry in WalkDir::new(&staged_root).into_iter().filter_map(Result::ok) {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let path = relative(&staged_root, entry.path()).unwrap_or_default();
+        anyhow::ensure!(listed.contains(path.as_str()), "{} is not listed in the manifest", path);
+    }
+
+    Ok(manifest)
+}
+
+/// An absolute source path in processing.json points into the exporting machine's data root.
+async fn relocate(staged_root: &Path, from: &Path, to: &Path) -> Result<()> {
+    for entry in WalkDir::new(staged_root).into_iter().filter_map(Result::ok) {
+        if entry.file_name() != "processing.json" {
+            continue;
+        }
+        let raw = fs::read_to_string(entry.path()).await?;
+        let value: Value = serde_json::from_str(&raw)
+            .with_context(|| format!("invalid {}", entry.path().display()))?;
+        let Some(rest) = value
+            .get("audio_file")
+            .and_then(Value::as_str)
+            .and_then(|audio| Path::new(audio).strip_prefix(from).ok())
+        else {
+            continue;
+        };
+
+        set_audio_file(entry.path(), value.clone(), &to.join(rest)).await?;
+    }
+    Ok(())
+}
+
+/// Backups of older versions are upgraded on the way, the file keeps its schema_version.
+async fn set_audio_file(processing: &Path, value: Value, audio_file: &Path) -> Result<()> {
+    let mut value = schema::upgrade(value, &PROCESSING)?;
+    value["audio_file"] = json!(audio_file.to_string_lossy());
+    write_atomic(processing, schema::to_json(&PROCESSING, &value)?.as_bytes())
+        .await
+        .with_context(|| format!("failed to write {}", processing.display()))
+}
+
+async fn replace(data_root: &Path, staged_root: &Path) -> Result<Option<PathBuf>> {
+    let _signals = lock_file(&data_root.join("signals.json")).await;
+    let _evidence = lock_file(&data_root.join("evidence.json")).await;
+    let _knowledge = lock_file(&data_root.join("knowledge.json")).await;
+
+    let previous = if data_root.exists() {
+        let previous = data_root.with_file_name(format!(
+            "data-before-import-{}",
+            Utc::now().format("%Y%m%dT%H%M%S")
+        ));
+        fs::rename(data_root, &previous)
+            .await
+            .with_context(|| format!("failed to move {} aside", data_root.display()))?;
+        Some(previous)
+    } else {
+        None
+    };
+
+    fs::rename(staged_root, data_root)
+        .await
+        .with_context(|| format!("failed to move the backup to {}", data_root.display()))?;
+    // the open connection still points at the database that moved aside
+    SqliteStorage::close(data_root);
+
+    Ok(previous)
+}
+
+/// Sessions are data/YYYY/MM/{stem}/ plus the source file {stem}.{ext}. A session with the same stem
+/// and document id exists already and is skipped; a different document with a taken stem gets the next free one.
+/// Knowledge and confirmations unknown here are added, then signals, evidence and knowledge are rebuilt.
+async fn merge(data_root: &Path, staged_root: &Path, manifest: &BackupManifest) -> Result<ImportSummary> {
+    if let Err(err) = create_snapshot(data_root, "auto", Some("before import".into())).await {
+        println!("⚠ snapshot before import failed → {}", err);
+    }
+
+    let mut summary = ImportSummary::default();
+    let mut sessions: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+    let mut others = Vec::new();
+    for file in &manifest.files {
+        match session_of(&file.path) {
+            Some(session) => sessions.entry(session).or_default().push(&file.path),
+            None => others.push(file.path.as_str()),
+        }
+    }
+
+    for (session, files) in sessions {
+        let (month, stem) = session.rsplit_once('/').context("invalid session path")?;
+        let month_dir = data_root.join(month);
+        fs::create_dir_all(&month_dir).await?;
+
+        let new_stem = if !stem_taken(&month_dir, stem).await? {
+            stem.to_string()
+        } else if same_document(&staged_root.join(&session), &month_dir.join(stem)).await {
+            summary.sessions_skipped += 1;
+            continue;
+        } else {
+            next_free_stem(&month_dir)?
+        };
+
+        let mut source_file = None;
+        for path in files {
+            let rest = &path[session.len()..];
+            let target = month_dir.join(format!("{}{}", new_stem, rest));
+            copy(&staged_root.join(path), &target).await?;
+            if rest.starts_with('.') {
+                source_file = Some(target);
+            }
+        }
+
+        if new_stem != stem {
+            let processing = month_dir.join(&new_stem).join("processing.json");
+            if let (Some(source_file), true) = (source_file, processing.exists()) {
+                let value: Value = serde_json::from_str(&fs::read_to_string(&processing).await?)?;
+                // usually only the file name next to the record dir is stored
+                let absolute = value
+                    .get("audio_file")
+                    .and_then(Value::as_str)
+                    .is_some_and(|audio| Path::new(audio).is_absolute());
+                let audio_file = if absolute {
+                    source_file
+                } else {
+                    PathBuf::from(source_file.file_name().context("source file has no name")?)
+                };
+                set_audio_file(&processing, value, &audio_file).await?;
+            }
+            println!("▶ backup: {} imported as {}/{}", session, month, new_stem);
+        }
+        summary.sessions_added += 1;
+    }
+
+    // files in the data root are global state, merged below
+    for path in others.into_iter().filter(|p| p.contains('/')) {
+        let target = data_root.join(path);
+        if !target.exists() {
+            copy(&staged_root.join(path), &target).await?;
+        }
+    }
+
+    {
+        let imported = EvidenceStore::load_or_create(staged_root).await?;
+        let mut local = EvidenceStore::lock_and_load(data_root).await?;
+        let known: HashSet<String> = local.all().iter().map(|r| r.key.clone()).collect();
+        for record in imported.all() {
+            if record.is_confirmation() && !known.contains(&record.key) {
+                local.insert_or_merge(record.clone());
+                summary.confirmations_added += 1;
+            }
+        }
+        if summary.confirmations_added > 0 {
+            local.save().await?;
+        }
+    }
+
+    {
+        let imported = KnowledgeStore::load_or_create(staged_root).await?;
+        let mut local = KnowledgeStore::lock_and_load(data_root).await?;
+        for record in imported.all() {
+            if local.get_by_id(&record.id).is_none() {
+                local.restore_version(&record.id, Some(record.clone()));
+                summary.knowledge_added += 1;
+            }
+        }
+        if summary.knowledge_added > 0 {
+            local.changed_by("import", "merge backup");
+            local.save().await?;
+        }
+    }
+
+    rebuild_global_state(data_root)
+        .await
+        .map_err(anyhow::Error::msg)?;
+
+    Ok(summary)
+}
+
+/// "YYYY/MM/{stem}" for files of a session, None for everything else.
+fn session_of(path: &str) -> Option<String> {
+    let parts: Vec<&str> = path.split('/').collect();
+    if parts.len() < 3
+        || parts[0].len() != 4
+        || parts[1].len() != 2
+        || !parts[0].chars().chain(parts[1].chars()).all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+
+    let stem = if parts.len() == 3 {
+        Path::new(parts[2]).file_stem()?.to_str()?
+    } else {
+        parts[2]
+    };
+    Some(format!("{}/{}/{}", parts[0], parts[1], stem))
+}
+
+async fn stem_taken(month_dir: &Path, stem: &str) -> Result<bool> {
+    let mut entries = fs::read_dir(month_dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        if entry.path().file_stem().is_some_and(|s| s == stem) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Sessions without a readable processing.json count as different, they are imported under a new stem.
+async fn same_document(staged: &Path, local: &Path) -> bool {
+    match (load_document_id(staged).await, load_document_id(local).await) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+async fn copy(source: &Path, target: &Path) -> Result<()> {
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+    fs::copy(source, target)
+        .await
+        .with_context(|| format!("failed to copy to {}", target.display()))?;
+    Ok(())
+}
@@ -0,0 +1,62 @@
+pub mod export;
+pub mod import;
+
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path};
+
+use crate::jobs::watcher::AUDIO_EXTENSIONS;
+
+/// Marks the archive as a VIA backup, checked before anything is restored.
+const FORMAT: &str = "via-backup";
+const MANIFEST: &str = "manifest.json";
+
+/// manifest.json at the archive root. Files are stored under data/ with paths relative to the data root.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BackupManifest {
+    pub format: String,
+    pub app_version: String,
+    pub created_at: String,
+    /// Data root of the exporting machine, absolute paths in processing.json are rewritten on import.
+    pub data_root: String,
+    pub include_audio: bool,
+    pub files: Vec<BackupFile>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BackupFile {
+    /// Relative to the data root, always with `/`.
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+/// The SQLite mirror and temp files of interrupted writes are left out, the mirror is rebuilt on import.
+fn include_in_backup(path: &str, include_audio: bool) -> bool {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    if name.starts_with("via.db") || name.ends_with(".tmp") {
+        return false;
+    }
+
+    include_audio
+        || !Path::new(name)
+            .extension()
+            .map(|e| e.to_string_lossy().to_ascii_lowercase())
+            .is_some_and(|e| AUDIO_EXTENSIONS.contains(&e.as_str()))
+}
+
+/// Manifest paths must stay inside the data root.
+fn is_safe(path: &str) -> bool {
+    !path.is_empty()
+        && Path::new(path)
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)))
+}
+
+fn relative(data_root: &Path, path: &Path) -> Option<String> {
+    let rel = path.strip_prefix(data_root).ok()?;
+    let parts: Vec<String> = rel
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+    Some(parts.join("/"))
+}
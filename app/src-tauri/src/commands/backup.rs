@@ -0,0 +1,41 @@
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+use crate::backup::export;
+use crate::backup::BackupManifest;
+use crate::backup::import::{self, ImportMode, ImportSummary};
+
+/// Writes the whole data directory into one archive at `path` (.tar.gz).
+#[tauri::command]
+pub async fn export_backup(
+    app: AppHandle,
+    path: String,
+    include_audio: bool,
+) -> Result<BackupManifest, String> {
+    let data_root = app
+        .path()
+        .app_data_dir()
+        .map_err(|_| "app data dir not available")?
+        .join("data");
+
+    export::export_backup(&data_root, &PathBuf::from(path), include_audio)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Validates the archive at `path` and restores it, mode: merge | replace.
+#[tauri::command]
+pub async fn import_backup(
+    app: AppHandle,
+    path: String,
+    mode: ImportMode,
+) -> Result<ImportSummary, String> {
+    let data_root = app
+        .path()
+        .app_data_dir()
+        .map_err(|_| "app data dir not available")?
+        .join("data");
+
+    import::import_backup(&data_root, &PathBuf::from(path), mode)
+        .await
+        .map_err(|e| e.to_string())
+}
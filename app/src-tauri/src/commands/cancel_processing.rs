@@ -0,0 +1,34 @@
+use tauri::{AppHandle, Manager, State};
+use crate::jobs::queue::JobQueue;
+use crate::state::AppState;
+
+/// Stops a running job, or drops a job still waiting in the queue.
+/// Steps cut off are marked cancelled in processing.json, the stores stay as they were.
+#[tauri::command]
+pub async fn cancel_processing(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    job_id: String,
+) -> Result<(), String> {
+    if state.jobs.cancel(&job_id) {
+        println!("▶ cancel_processing: job {} cancelled", job_id);
+        return Ok(());
+    }
+
+    let data_root = app
+        .path()
+        .app_data_dir()
+        .map_err(|_| "app data dir not available")?
+        .join("data");
+
+    let cancelled = JobQueue::update(&data_root, |q| q.cancel_waiting(&job_id))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if !cancelled {
+        return Err(format!("job {} is not running or queued", job_id));
+    }
+
+    println!("▶ cancel_processing: queued job {} removed", job_id);
+    Ok(())
+}
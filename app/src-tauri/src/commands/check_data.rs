@@ -0,0 +1,16 @@
+use tauri::{AppHandle, Manager};
+use crate::processing::integrity::{self, DataCheckReport};
+
+/// Reports problems in the data directory, `repair` fixes what can be fixed without losing data.
+#[tauri::command]
+pub async fn check_data(app: AppHandle, repair: bool) -> Result<DataCheckReport, String> {
+    let data_root = app
+        .path()
+        .app_data_dir()
+        .map_err(|_| "app data dir not available")?
+        .join("data");
+
+    integrity::check_data(&data_root, repair)
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,48 @@
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+use crate::jobs::queue::JobQueue;
+use crate::jobs::worker::{execute_job, JobOutcome};
+use crate::pipeline::report::PipelineReport;
+use crate::processing::ingest::{ingest_note, ingest_text_file};
+
+/// Creates a text session from a file (.txt, .md, .eml) or from pasted `content`
+/// and runs extraction, evidence and knowledge steps on it like on a transcript.
+#[tauri::command]
+pub async fn ingest_text(
+    app: AppHandle,
+    path: Option<PathBuf>,
+    content: Option<String>,
+    title: Option<String>,
+) -> Result<PipelineReport, String> {
+    let data_root = app
+        .path()
+        .app_data_dir()
+        .map_err(|_| "app data dir not available")?
+        .join("data");
+
+    let source = match (path, content) {
+        (Some(path), _) => ingest_text_file(&data_root, &path).await,
+        (None, Some(content)) => ingest_note(&data_root, title.as_deref(), &content).await,
+        (None, None) => return Err("either path or content is required".into()),
+    }
+        .map_err(|e| e.to_string())?;
+
+    let (job, claimed) = JobQueue::update(&data_root, |q| {
+        let job = q.enqueue(&source);
+        let claimed = q.claim(&job.id);
+        (job, claimed)
+    })
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if !claimed {
+        return Err(format!("document is already being processed (job {})", job.id));
+    }
+
+    match execute_job(&app, &data_root, &job).await? {
+        JobOutcome::Done(report) => Ok(report),
+        JobOutcome::Failed(err) => Err(err.to_string()),
+        JobOutcome::Parked(reason) => Err(format!("document queued for later processing: {}", reason)),
+        JobOutcome::Cancelled => Err(format!("processing cancelled (job {})", job.id)),
+    }
+}
@@ -0,0 +1,48 @@
+use tauri::{AppHandle, Manager, State};
+use crate::jobs::manager::JobInfo;
+use crate::jobs::queue::JobQueue;
+use crate::state::AppState;
+
+/// All jobs of the queue, newest first, with live progress for the running ones.
+#[tauri::command]
+pub async fn list_jobs(app: AppHandle, state: State<'_, AppState>) -> Result<Vec<JobInfo>, String> {
+    let data_root = app
+        .path()
+        .app_data_dir()
+        .map_err(|_| "app data dir not available")?
+        .join("data");
+
+    let queue = JobQueue::snapshot(&data_root)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(queue
+        .jobs
+        .into_iter()
+        .rev()
+        .map(|job| state.jobs.info(job))
+        .collect())
+}
+
+#[tauri::command]
+pub async fn get_job(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    job_id: String,
+) -> Result<JobInfo, String> {
+    let data_root = app
+        .path()
+        .app_data_dir()
+        .map_err(|_| "app data dir not available")?
+        .join("data");
+
+    let job = JobQueue::snapshot(&data_root)
+        .await
+        .map_err(|e| e.to_string())?
+        .jobs
+        .into_iter()
+        .find(|j| j.id == job_id)
+        .ok_or_else(|| format!("job {} not found", job_id))?;
+
+    Ok(state.jobs.info(job))
+}
@@ -2,7 +2,8 @@ use chrono::Utc;
 use serde::Serialize;
 use tauri::{AppHandle, Manager};
 
-use crate::store::knowledge::KnowledgeStore;
+use crate::storage::{open_storage, KnowledgeQuery};
+use crate::store::lifecycle::{EntityType, KnowledgeStatus};
 fn node_id(typ: &str, value: &str) -> String {
     format!(
         "{}:{}",
@@ -49,7 +50,7 @@ fn infer_object_type(r: &crate::store::knowledge::KnowledgeRecord) -> String {
         return "organization".into();
     }
 
-    if r.subject_type == "event" {
+    if r.subject_type == EntityType::Event {
         // Event → Zeit, Datum, Aktion
         return "event".into();
     }
@@ -66,7 +67,13 @@ pub async fn get_knowledge_graph(app: AppHandle) -> Result<GraphModel, String> {
 
     println!("▶ get_knowledge_graph");
 
-    let knowledge = KnowledgeStore::load_or_create(&data_root)
+    let storage = open_storage(&data_root).await.map_err(|e| e.to_string())?;
+    let knowledge = storage
+        .knowledge(&KnowledgeQuery {
+            exclude_status: Some(KnowledgeStatus::Deprecated),
+            min_confidence: Some(1.0),
+            ..Default::default()
+        })
         .await
         .map_err(|e| e.to_string())?;
 
@@ -75,22 +82,14 @@ pub async fn get_knowledge_graph(app: AppHandle) -> Result<GraphModel, String> {
     let mut nodes: HashMap<String, GraphNode> = HashMap::new();
     let mut edges: Vec<GraphEdge> = Vec::new();
 
-    for r in knowledge.all() {
-        if r.status == "deprecated" {
-            continue;
-        }
-
-        if r.confidence < 1.0 {
-            continue;
-        }
-
-        let subject_node_id = node_id(&r.subject_type, &r.subject_value);
+    for r in &knowledge {
+        let subject_node_id = node_id(r.subject_type.as_str(), &r.subject_value);
         let object_node_id = node_id("object", &r.object_value);
 
         nodes.entry(subject_node_id.clone()).or_insert(GraphNode {
             id: subject_node_id.clone(),
             label: r.subject_value.clone(),
-            node_type: r.subject_type.clone(),
+            node_type: r.subject_type.to_string(),
         });
         let object_type = infer_object_type(r); // small hack to demonstrate
 
@@ -0,0 +1,82 @@
+use tauri::{AppHandle, Manager};
+use crate::store::history::{find_change, record_history, KnowledgeChange};
+use crate::store::knowledge::KnowledgeStore;
+
+/// All changes of one knowledge record, oldest first.
+#[tauri::command]
+pub async fn get_record_history(
+    app: AppHandle,
+    knowledge_id: String,
+) -> Result<Vec<KnowledgeChange>, String> {
+    let data_root = app
+        .path()
+        .app_data_dir()
+        .map_err(|_| "app data dir not available")?
+        .join("data");
+
+    record_history(&data_root, &knowledge_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Reverts one change. Only the latest change of a record can be undone,
+/// later changes would otherwise be lost silently.
+#[tauri::command]
+pub async fn undo_change(app: AppHandle, change_id: String) -> Result<(), String> {
+    let data_root = app
+        .path()
+        .app_data_dir()
+        .map_err(|_| "app data dir not available")?
+        .join("data");
+
+    println!("▶ undo_change {}", change_id);
+
+    let change = find_change(&data_root, &change_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut knowledge = KnowledgeStore::lock_and_load(&data_root)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let current = knowledge.get_by_id(&change.record_id).map(serde_json::to_value).transpose();
+    let after = change.after.as_ref().map(serde_json::to_value).transpose();
+    if current.map_err(|e| e.to_string())? != after.map_err(|e| e.to_string())? {
+        return Err("record changed since, undo the newer changes first".into());
+    }
+
+    knowledge.restore_version(&change.record_id, change.before);
+    knowledge.changed_by("user", &format!("undo {}", change_id));
+    knowledge.save().await.map_err(|e| e.to_string())
+}
+
+/// Puts a record back to its state right after `change_id`. The restore is a change itself.
+#[tauri::command]
+pub async fn restore_record(
+    app: AppHandle,
+    knowledge_id: String,
+    change_id: String,
+) -> Result<(), String> {
+    let data_root = app
+        .path()
+        .app_data_dir()
+        .map_err(|_| "app data dir not available")?
+        .join("data");
+
+    println!("▶ restore_record {} to {}", knowledge_id, change_id);
+
+    let change = find_change(&data_root, &change_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    if change.record_id != knowledge_id {
+        return Err("change belongs to another record".into());
+    }
+
+    let mut knowledge = KnowledgeStore::lock_and_load(&data_root)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    knowledge.restore_version(&knowledge_id, change.after);
+    knowledge.changed_by("user", &format!("restore to {}", change_id));
+    knowledge.save().await.map_err(|e| e.to_string())
+}
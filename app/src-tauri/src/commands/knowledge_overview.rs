@@ -2,6 +2,7 @@ use serde::Serialize;
 use tauri::{AppHandle, Manager};
 use crate::pipeline::context::RecordContext;
 use crate::store::knowledge::KnowledgeStore;
+use crate::store::lifecycle::{EntityType, KnowledgeStatus};
 
 #[derive(Serialize)]
 pub struct KnowledgeOverview {
@@ -40,29 +41,22 @@ pub async fn get_knowledge_overview(app: AppHandle) -> Result<KnowledgeOverview,
     let mut relations = Vec::new();
 
     for record in knowledge.all() {
-        if record.status == "deprecated" {
-            continue;
-        }
-        if record.status != "approved" {
+        if record.status() != KnowledgeStatus::Approved {
             continue;
         }
         if record.confidence < 1.0 {
             continue;
         }
 
-        if record.status != "candidate" && record.status != "approved" {
-            continue;
-        }
-
         // subject sammeln
-        match record.subject_type.as_str() {
-            "person" => {
+        match record.subject_type {
+            EntityType::Person => {
                 persons.insert(record.subject_value.clone());
             }
-            "organization" => {
+            EntityType::Organization => {
                 organizations.insert(record.subject_value.clone());
             }
-            "event" => {
+            EntityType::Event => {
                 events.insert(record.subject_value.clone());
             }
             _ => {}
@@ -8,3 +8,18 @@ pub mod knowledge_graph;
 pub mod process_recording;
 pub mod recording_stop;
 pub mod recording_start;
+pub mod usage_report;
+pub mod settings;
+pub mod prompts;
+pub mod cancel_processing;
+pub mod jobs;
+pub mod reprocess;
+pub mod ingest_text;
+pub mod rebuild_global_state;
+pub mod storage;
+pub mod knowledge_history;
+pub mod snapshots;
+pub mod backup;
+pub mod check_data;
+pub mod trash;
+pub mod retention;
@@ -1,27 +1,39 @@
-use crate::pipeline::context::RecordContext;
 use std::path::PathBuf;
-use std::sync::Arc;
-use tauri::AppHandle;
-use crate::processing::document::process_document;
-use crate::processing::progress::{emit_progress, TauriProgressEmitter};
-use crate::processing::state_global::update_global_state;
+use tauri::{AppHandle, Manager};
+use crate::jobs::queue::JobQueue;
+use crate::jobs::worker::{execute_job, JobOutcome};
+use crate::pipeline::report::PipelineReport;
+
+/// Queues the recording and processes it right away.
+/// The report lists failed non-critical steps, a failed critical step is an error.
+/// Offline the job stays parked and the background worker picks it up later.
 #[tauri::command]
-pub async fn process_recording(app: AppHandle, audio_path: PathBuf) -> Result<(), String> {
-    let base_dir = audio_path
-        .parent()
-        .ok_or("audio file has no parent directory")?
-        .to_path_buf();
-    let progress = TauriProgressEmitter::new(app.clone());
+pub async fn process_recording(
+    app: AppHandle,
+    audio_path: PathBuf,
+) -> Result<PipelineReport, String> {
+    let data_root = app
+        .path()
+        .app_data_dir()
+        .map_err(|_| "app data dir not available")?
+        .join("data");
+
+    let (job, claimed) = JobQueue::update(&data_root, |q| {
+        let job = q.enqueue(&audio_path);
+        let claimed = q.claim(&job.id);
+        (job, claimed)
+    })
+        .await
+        .map_err(|e| e.to_string())?;
 
-    let ctx = RecordContext {
-        base_dir: base_dir.clone(),
-        audio_file: audio_path,
-        progress: Some(Arc::new(progress)),
-    };
-    emit_progress(&app, "start", "Verarbeitung gestartet", 5);
+    if !claimed {
+        return Err(format!("recording is already being processed (job {})", job.id));
+    }
 
-    process_document(&ctx).await?;
-    update_global_state(&ctx).await?;
-    emit_progress(&app, "done", "Fertig", 100);
-    Ok(())
+    match execute_job(&app, &data_root, &job).await? {
+        JobOutcome::Done(report) => Ok(report),
+        JobOutcome::Failed(err) => Err(err.to_string()),
+        JobOutcome::Parked(reason) => Err(format!("recording queued for later processing: {}", reason)),
+        JobOutcome::Cancelled => Err(format!("processing cancelled (job {})", job.id)),
+    }
 }
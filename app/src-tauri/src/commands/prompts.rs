@@ -0,0 +1,40 @@
+use tauri::{AppHandle, Manager};
+use crate::prompts::ab_test::{run_ab_test, AbTestResult};
+use crate::prompts::registry::PromptRegistry;
+use crate::prompts::template::PromptTemplate;
+
+#[tauri::command]
+pub async fn list_prompts(app: AppHandle) -> Result<Vec<PromptTemplate>, String> {
+    let data_root = app
+        .path()
+        .app_data_dir()
+        .map_err(|_| "app data dir not available")?
+        .join("data");
+
+    let registry = PromptRegistry::load(&data_root)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(registry.all().into_iter().cloned().collect())
+}
+
+#[tauri::command]
+pub async fn ab_test_prompt(
+    app: AppHandle,
+    doc_id: String,
+    prompt: String,
+    version_a: String,
+    version_b: String,
+) -> Result<AbTestResult, String> {
+    let data_root = app
+        .path()
+        .app_data_dir()
+        .map_err(|_| "app data dir not available")?
+        .join("data");
+
+    println!("▶ ab_test_prompt {} {} {} vs {}", doc_id, prompt, version_a, version_b);
+
+    run_ab_test(&data_root, &doc_id, &prompt, &version_a, &version_b)
+        .await
+        .map_err(|e| e.to_string())
+}
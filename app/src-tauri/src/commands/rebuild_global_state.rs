@@ -0,0 +1,14 @@
+use tauri::{AppHandle, Manager};
+use crate::processing::state_global::rebuild_global_state as run_rebuild;
+
+/// Maintenance: rebuilds signals, evidence and knowledge from all sessions.
+#[tauri::command]
+pub async fn rebuild_global_state(app: AppHandle) -> Result<(), String> {
+    let data_root = app
+        .path()
+        .app_data_dir()
+        .map_err(|_| "app data dir not available")?
+        .join("data");
+
+    run_rebuild(&data_root).await
+}
@@ -0,0 +1,20 @@
+use tauri::{AppHandle, Manager};
+use crate::processing::reprocess::{reprocess_sessions as run_reprocess, ReprocessSummary, SessionFilter};
+
+/// Queues the sessions matching `filter` to rerun the given steps (all if empty).
+#[tauri::command]
+pub async fn reprocess_sessions(
+    app: AppHandle,
+    filter: SessionFilter,
+    steps: Vec<String>,
+) -> Result<ReprocessSummary, String> {
+    let data_root = app
+        .path()
+        .app_data_dir()
+        .map_err(|_| "app data dir not available")?
+        .join("data");
+
+    run_reprocess(&data_root, &filter, &steps)
+        .await
+        .map_err(|e| e.to_string())
+}
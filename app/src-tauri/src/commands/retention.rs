@@ -0,0 +1,16 @@
+use tauri::{AppHandle, Manager};
+use crate::jobs::retention::{self, RetentionReport};
+
+/// Applies the retention policies now instead of waiting for the daily run.
+#[tauri::command]
+pub async fn apply_retention(app: AppHandle) -> Result<RetentionReport, String> {
+    let data_root = app
+        .path()
+        .app_data_dir()
+        .map_err(|_| "app data dir not available")?
+        .join("data");
+
+    retention::apply_retention(&data_root)
+        .await
+        .map_err(|e| e.to_string())
+}
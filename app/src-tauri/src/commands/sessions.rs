@@ -1,8 +1,8 @@
 use serde::Serialize;
 use tauri::{AppHandle, Manager};
-use walkdir::WalkDir;
 use tokio::fs;
 use crate::processing::processing::ProcessingFile;
+use crate::storage::open_storage;
 #[derive(Serialize)]
 pub struct SessionEntity {
     pub entity_type: String,
@@ -15,6 +15,8 @@ pub struct SessionSummary {
     pub title: String,
     pub raw: String,
     pub entities: Vec<SessionEntity>,
+    /// The transcript was removed by a retention policy, `raw` is empty.
+    pub purged: bool,
 }
 
 #[tauri::command]
@@ -22,49 +24,62 @@ pub async fn list_sessions(app: AppHandle) -> Result<Vec<SessionSummary>, String
     let data_root = app
         .path()
         .app_data_dir()
-        .map_err(|_| "app data dir not available")?;
+        .map_err(|_| "app data dir not available")?
+        .join("data");
 
     println!("▶ list_sessions scanning {}", data_root.display());
 
+    let storage = open_storage(&data_root).await.map_err(|e| e.to_string())?;
+    let rows = storage.sessions().await.map_err(|e| e.to_string())?;
+
     let mut sessions = Vec::new();
 
-    for entry in WalkDir::new(&data_root)
-        .into_iter()
-        .filter_map(Result::ok)
-        .filter(|e| e.file_type().is_dir())
-    {
-        let record_dir = entry.path();
+    for row in rows {
+        let record_dir = row.record_dir.as_path();
 
         let entities_path = record_dir.join("entities.json");
-        let processing_path = record_dir.join("processing.json");
         let text_path = record_dir.join("text.txt");
 
-        if !processing_path.exists() || !text_path.exists() || !entities_path.exists() {
+        if !entities_path.exists() {
             continue;
         }
-
-
-
-
-        let processing =
-            ProcessingFile::load(&record_dir)
+        let purged = !text_path.exists();
+        if purged {
+            let transcript_purged = ProcessingFile::load(record_dir)
                 .await
-                .map_err(|e| e.to_string())?;
-
+                .is_ok_and(|p| p.transcript_purged());
+            if !transcript_purged {
+                continue;
+            }
+        }
 
-        let date = processing.started_at;
-        let id = processing.doc_id;
-        
-        let raw = fs::read_to_string(&text_path)
-            .await
-            .map_err(|e| e.to_string())?;
+        let date = row.started_at;
+        let id = row.doc_id;
+
+        // one unreadable session must not hide the others, check_data reports it
+        let raw = if purged {
+            String::new()
+        } else {
+            match fs::read_to_string(&text_path).await {
+                Ok(raw) => raw,
+                Err(err) => {
+                    println!("⚠ list_sessions: skipping {} → {}", text_path.display(), err);
+                    continue;
+                }
+            }
+        };
 
-        let entities_raw = fs::read_to_string(&entities_path)
+        let entities_json: serde_json::Value = match fs::read_to_string(&entities_path)
             .await
-            .map_err(|e| e.to_string())?;
-
-        let entities_json: serde_json::Value =
-            serde_json::from_str(&entities_raw).map_err(|e| e.to_string())?;
+            .map_err(anyhow::Error::from)
+            .and_then(|raw| Ok(serde_json::from_str(&raw)?))
+        {
+            Ok(json) => json,
+            Err(err) => {
+                println!("⚠ list_sessions: skipping {} → {}", entities_path.display(), err);
+                continue;
+            }
+        };
         
         use std::collections::HashSet;
 
@@ -105,6 +120,7 @@ pub async fn list_sessions(app: AppHandle) -> Result<Vec<SessionSummary>, String
             title,
             raw,
             entities,
+            purged,
         });
     }
 
@@ -0,0 +1,31 @@
+use tauri::{AppHandle, Manager};
+use crate::store::settings::Settings;
+
+#[tauri::command]
+pub async fn get_settings(app: AppHandle) -> Result<Settings, String> {
+    let data_root = app
+        .path()
+        .app_data_dir()
+        .map_err(|_| "app data dir not available")?
+        .join("data");
+
+    Settings::load_or_create(&data_root)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn update_settings(app: AppHandle, settings: Settings) -> Result<(), String> {
+    let data_root = app
+        .path()
+        .app_data_dir()
+        .map_err(|_| "app data dir not available")?
+        .join("data");
+
+    println!("▶ update_settings {}", data_root.display());
+
+    settings
+        .save(&data_root)
+        .await
+        .map_err(|e| e.to_string())
+}
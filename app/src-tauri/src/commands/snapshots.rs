@@ -0,0 +1,58 @@
+use tauri::{AppHandle, Manager};
+use crate::store::snapshot::{self, KnowledgeDiff, SnapshotInfo};
+
+#[tauri::command]
+pub async fn create_snapshot(app: AppHandle, label: Option<String>) -> Result<SnapshotInfo, String> {
+    let data_root = app
+        .path()
+        .app_data_dir()
+        .map_err(|_| "app data dir not available")?
+        .join("data");
+
+    snapshot::create_snapshot(&data_root, "manual", label)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Oldest first.
+#[tauri::command]
+pub async fn list_snapshots(app: AppHandle) -> Result<Vec<SnapshotInfo>, String> {
+    let data_root = app
+        .path()
+        .app_data_dir()
+        .map_err(|_| "app data dir not available")?
+        .join("data");
+
+    snapshot::list_snapshots(&data_root)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// `from` and `to` are snapshot ids or "current".
+#[tauri::command]
+pub async fn diff_knowledge(app: AppHandle, from: String, to: String) -> Result<KnowledgeDiff, String> {
+    let data_root = app
+        .path()
+        .app_data_dir()
+        .map_err(|_| "app data dir not available")?
+        .join("data");
+
+    snapshot::diff_knowledge(&data_root, &from, &to)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn restore_snapshot(app: AppHandle, snapshot_id: String) -> Result<(), String> {
+    let data_root = app
+        .path()
+        .app_data_dir()
+        .map_err(|_| "app data dir not available")?
+        .join("data");
+
+    println!("▶ restore_snapshot {}", snapshot_id);
+
+    snapshot::restore_snapshot(&data_root, &snapshot_id)
+        .await
+        .map_err(|e| e.to_string())
+}
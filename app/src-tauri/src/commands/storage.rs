@@ -0,0 +1,16 @@
+use tauri::{AppHandle, Manager};
+use crate::storage::migrate::{migrate_json_to_sqlite, MigrationSummary};
+
+/// One-shot: copies the JSON state into the SQLite database and switches to it.
+#[tauri::command]
+pub async fn migrate_storage(app: AppHandle) -> Result<MigrationSummary, String> {
+    let data_root = app
+        .path()
+        .app_data_dir()
+        .map_err(|_| "app data dir not available")?
+        .join("data");
+
+    migrate_json_to_sqlite(&data_root)
+        .await
+        .map_err(|e| e.to_string())
+}
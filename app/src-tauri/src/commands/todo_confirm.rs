@@ -1,12 +1,8 @@
-use std::sync::Arc;
-use chrono::Utc;
 use tauri::{AppHandle, Manager};
 use crate::store::evidence::{EvidenceRecord, EvidenceStore};
 use crate::store::knowledge::KnowledgeStore;
+use crate::store::lifecycle::{Approver, KnowledgeStatus};
 use crate::pipeline::knowledge_builder::KnowledgeBuilder;
-use crate::pipeline::pipeline::Pipeline;
-use crate::pipeline::context::RecordContext;
-use crate::processing::progress::TauriProgressEmitter;
 
 #[tauri::command]
 pub async fn confirm_todo(
@@ -23,7 +19,11 @@ pub async fn confirm_todo(
         knowledge_id
     );
 
-    let knowledge = KnowledgeStore::load_or_create(&data_root)
+    // evidence before knowledge, like the pipeline, so writers never wait on each other crosswise
+    let mut evidence = EvidenceStore::lock_and_load(&data_root)
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut knowledge = KnowledgeStore::lock_and_load(&data_root)
         .await
         .map_err(|e| e.to_string())?;
 
@@ -36,32 +36,20 @@ pub async fn confirm_todo(
         record.id
     );
 
-    let mut evidence = EvidenceStore::load_or_create(&data_root)
-        .await
-        .map_err(|e| e.to_string())?;
-
     let ev = EvidenceRecord::from_user_confirmation(&record);
     evidence.insert_or_merge(ev);
     evidence.save().await.map_err(|e| e.to_string())?;
-    let mut knowledge = KnowledgeStore::load_or_create(&data_root).await.map_err(|e| e.to_string())?;
+    drop(evidence);
 
-    if let Some(r) = knowledge.get_mut(&knowledge_id) {
-        r.status = "approved".into();
-        r.approved_by = "user".into();
-        r.updated_at = Some(Utc::now().to_rfc3339());
-    }
-    let progress = TauriProgressEmitter::new(app.clone());
+    knowledge
+        .transition(&knowledge_id, KnowledgeStatus::Approved, Approver::User)
+        .map_err(|e| e.to_string())?;
 
+    knowledge.changed_by("user", "confirmed");
     knowledge.save().await.map_err(|e| e.to_string())?;
-    let ctx = RecordContext {
-        base_dir: data_root.clone(),
-        audio_file: data_root.clone(),
-        progress: Some(Arc::new(progress)),
-    };
+    drop(knowledge);
 
-    Pipeline::new()
-        .add_step(KnowledgeBuilder)
-        .run(&ctx)
+    KnowledgeBuilder::build(&data_root)
         .await
         .map_err(|e| e.to_string())?;
     Ok(())
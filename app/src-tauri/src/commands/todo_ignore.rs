@@ -1,6 +1,6 @@
-use chrono::Utc;
 use tauri::{AppHandle, Manager};
 use crate::store::knowledge::KnowledgeStore;
+use crate::store::lifecycle::{Approver, KnowledgeStatus};
 use serde_json::Value;
 
 #[tauri::command]
@@ -16,23 +16,20 @@ pub async fn ignore_todo(
 
     println!("▶ todo ignore_todo {}", knowledge_id);
 
-    let mut knowledge = KnowledgeStore::load_or_create(&data_root)
+    let mut knowledge = KnowledgeStore::lock_and_load(&data_root)
         .await
         .map_err(|e| e.to_string())?;
 
     let record = knowledge
-        .get_mut(&knowledge_id)
-        .ok_or("knowledge record not found")?;
-
-    record.status = "deprecated".into();
-    record.approved_by = "user".into();
-    record.updated_at = Some(Utc::now().to_rfc3339());
+        .transition(&knowledge_id, KnowledgeStatus::Deprecated, Approver::User)
+        .map_err(|e| e.to_string())?;
 
     record.extra.insert(
         "deprecated_reason".into(),
         Value::String("user_ignored".into())
     );
 
+    knowledge.changed_by("user", "ignored");
     knowledge.save().await.map_err(|e| e.to_string())?;
 
     Ok(())
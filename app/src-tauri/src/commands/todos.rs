@@ -1,5 +1,6 @@
 // src-tauri/src/commands/todos.rs
-use crate::store::knowledge::KnowledgeStore;
+use crate::storage::{open_storage, KnowledgeQuery};
+use crate::store::lifecycle::{Approver, KnowledgeStatus};
 use serde::Serialize;
 use tauri::{AppHandle, Manager};
 
@@ -31,19 +32,19 @@ pub async fn list_todos(app: AppHandle) -> Result<Vec<TodoItem>, String> {
 
     println!("▶ list_todos scanning {}", data_root.display());
 
-    let knowledge = KnowledgeStore::load_or_create(&data_root)
+    let storage = open_storage(&data_root).await.map_err(|e| e.to_string())?;
+    let candidates = storage
+        .knowledge(&KnowledgeQuery {
+            status: Some(KnowledgeStatus::Candidate),
+            approved_by: Some(Approver::None),
+            ..Default::default()
+        })
         .await
         .map_err(|e| e.to_string())?;
 
     let mut todos = Vec::new();
 
-    for record in knowledge.all() {
-        if record.status != "candidate" {
-            continue;
-        }
-        if record.approved_by != "none" {
-            continue;
-        }
+    for record in &candidates {
 
         let date = record
             .updated_at
@@ -76,7 +77,7 @@ pub async fn list_todos(app: AppHandle) -> Result<Vec<TodoItem>, String> {
             source_document: source_document
         });
     }
-    println!("▶ list_todos done count {} knowledge count {}", todos.len(), knowledge.all().len());
+    println!("▶ list_todos done count {} ({} storage)", todos.len(), storage.name());
     // neueste zuerst
     todos.sort_by(|a, b| b.date.cmp(&a.date));
 
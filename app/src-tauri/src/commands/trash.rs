@@ -0,0 +1,56 @@
+use tauri::{AppHandle, Manager};
+use crate::processing::trash::{self, TrashEntry};
+
+/// Moves the session to the trash and takes it out of signals, evidence and knowledge.
+#[tauri::command]
+pub async fn delete_session(app: AppHandle, doc_id: String) -> Result<TrashEntry, String> {
+    let data_root = app
+        .path()
+        .app_data_dir()
+        .map_err(|_| "app data dir not available")?
+        .join("data");
+
+    trash::delete_session(&data_root, &doc_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn list_trash(app: AppHandle) -> Result<Vec<TrashEntry>, String> {
+    let data_root = app
+        .path()
+        .app_data_dir()
+        .map_err(|_| "app data dir not available")?
+        .join("data");
+
+    trash::list_trash(&data_root)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn restore_session(app: AppHandle, doc_id: String) -> Result<(), String> {
+    let data_root = app
+        .path()
+        .app_data_dir()
+        .map_err(|_| "app data dir not available")?
+        .join("data");
+
+    trash::restore_session(&data_root, &doc_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Deletes everything in the trash for good.
+#[tauri::command]
+pub async fn empty_trash(app: AppHandle) -> Result<usize, String> {
+    let data_root = app
+        .path()
+        .app_data_dir()
+        .map_err(|_| "app data dir not available")?
+        .join("data");
+
+    trash::empty_trash(&data_root)
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,17 @@
+use tauri::{AppHandle, Manager};
+use crate::usage::report::UsageReport;
+
+#[tauri::command]
+pub async fn get_usage_report(app: AppHandle) -> Result<UsageReport, String> {
+    let data_root = app
+        .path()
+        .app_data_dir()
+        .map_err(|_| "app data dir not available")?
+        .join("data");
+
+    println!("▶ get_usage_report scanning {}", data_root.display());
+
+    UsageReport::build(&data_root)
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,117 @@
+use chrono::Utc;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio_util::sync::CancellationToken;
+
+use crate::jobs::queue::QueuedJob;
+use crate::processing::progress::ProgressEvent;
+
+/// Sessions processed at the same time. Each one already runs its relation agents in parallel.
+const MAX_CONCURRENT_JOBS: usize = 2;
+
+/// Last progress event of a running job.
+#[derive(Debug, Serialize, Clone)]
+pub struct JobProgress {
+    pub doc_id: Option<String>,
+    pub stage: String,
+    pub message: String,
+    pub percent: u8,
+    pub updated_at: String,
+}
+
+impl From<&ProgressEvent> for JobProgress {
+    fn from(event: &ProgressEvent) -> Self {
+        Self {
+            doc_id: event.doc_id.clone(),
+            stage: event.stage.clone(),
+            message: event.message.clone(),
+            percent: event.percent,
+            updated_at: Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+/// Queue entry plus live progress, returned by `list_jobs` / `get_job`.
+#[derive(Debug, Serialize)]
+pub struct JobInfo {
+    #[serde(flatten)]
+    pub job: QueuedJob,
+    pub progress: Option<JobProgress>,
+}
+
+struct RunningJob {
+    cancel: CancellationToken,
+    progress: Option<JobProgress>,
+}
+
+/// In-process side of the job queue: concurrency slots, cancellation and live progress.
+/// queue.json stays the source of truth for job status.
+pub struct JobManager {
+    slots: Arc<Semaphore>,
+    running: Mutex<HashMap<String, RunningJob>>,
+}
+
+impl Default for JobManager {
+    fn default() -> Self {
+        Self {
+            slots: Arc::new(Semaphore::new(MAX_CONCURRENT_JOBS)),
+            running: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl JobManager {
+    pub fn register(&self, job_id: &str) -> CancellationToken {
+        let token = CancellationToken::new();
+        self.running.lock().unwrap().insert(
+            job_id.to_string(),
+            RunningJob {
+                cancel: token.clone(),
+                progress: None,
+            },
+        );
+        token
+    }
+
+    pub fn remove(&self, job_id: &str) {
+        self.running.lock().unwrap().remove(job_id);
+    }
+
+    /// Waits for a free processing slot, the job holds it until the permit is dropped.
+    pub async fn acquire(&self) -> OwnedSemaphorePermit {
+        self.slots
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("job semaphore is never closed")
+    }
+
+    /// False if the job is not running here.
+    pub fn cancel(&self, job_id: &str) -> bool {
+        match self.running.lock().unwrap().get(job_id) {
+            Some(job) => {
+                job.cancel.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn report(&self, job_id: &str, progress: JobProgress) {
+        if let Some(job) = self.running.lock().unwrap().get_mut(job_id) {
+            job.progress = Some(progress);
+        }
+    }
+
+    pub fn info(&self, job: QueuedJob) -> JobInfo {
+        let progress = self
+            .running
+            .lock()
+            .unwrap()
+            .get(&job.id)
+            .and_then(|r| r.progress.clone());
+        JobInfo { job, progress }
+    }
+}
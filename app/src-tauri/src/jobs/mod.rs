@@ -0,0 +1,5 @@
+pub mod manager;
+pub mod queue;
+pub mod watcher;
+pub mod worker;
+pub mod retention;
@@ -0,0 +1,212 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::pipeline::report::PipelineReport;
+use crate::store::atomic::write_atomic;
+use crate::store::schema::{self, QUEUE};
+
+/// Finished jobs (done, failed, cancelled) kept as history, older ones are dropped on save.
+const FINISHED_JOBS_KEPT: usize = 200;
+
+/// Serializes load-modify-save of queue.json inside this process.
+static QUEUE_LOCK: Mutex<()> = Mutex::const_new(());
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QueuedJob {
+    pub id: String,
+    /// Recording or stored source of a text session.
+    pub audio_file: PathBuf,
+    pub status: String, // pending | running | parked | done | failed | cancelled
+    pub attempts: u32,
+    pub last_error: Option<String>,
+    pub enqueued_at: String,
+    pub updated_at: String,
+    /// Step outcomes of the last run.
+    #[serde(default)]
+    pub report: Option<PipelineReport>,
+    /// Steps run even if up to date, set by reprocessing.
+    #[serde(default)]
+    pub force: Vec<String>,
+}
+
+impl QueuedJob {
+    pub fn is_finished(&self) -> bool {
+        matches!(self.status.as_str(), "done" | "failed" | "cancelled")
+    }
+}
+
+/// Persistent processing queue at {data_root}/queue.json.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct JobQueue {
+    pub jobs: Vec<QueuedJob>,
+}
+
+impl JobQueue {
+    async fn load(data_root: &Path) -> Result<Self> {
+        let path = data_root.join("queue.json");
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let loaded = schema::load(&path, &QUEUE).await?;
+        serde_json::from_value(loaded.value).context("invalid queue.json")
+    }
+
+    async fn save(&self, data_root: &Path) -> Result<()> {
+        let path = data_root.join("queue.json");
+        fs::create_dir_all(data_root).await?;
+
+        let json = schema::to_json(&QUEUE, self)?;
+        write_atomic(&path, json.as_bytes())
+            .await
+            .with_context(|| format!("failed to write {}", path.display()))?;
+
+        Ok(())
+    }
+
+    /// Current queue state, read only.
+    pub async fn snapshot(data_root: &Path) -> Result<Self> {
+        let _guard = QUEUE_LOCK.lock().await;
+        Self::load(data_root).await
+    }
+
+    /// Loads the queue, applies `f` and saves it again while holding the queue lock.
+    pub async fn update<T>(data_root: &Path, f: impl FnOnce(&mut JobQueue) -> T) -> Result<T> {
+        let _guard = QUEUE_LOCK.lock().await;
+        let mut queue = Self::load(data_root).await?;
+        let result = f(&mut queue);
+        queue.prune_finished();
+        queue.save(data_root).await?;
+        Ok(result)
+    }
+
+    /// Runs `f` while no job is open and none can be enqueued or claimed, for work that swaps out
+    /// the data root underneath the worker. Fails if a job is pending, running or parked.
+    pub async fn while_idle<T>(data_root: &Path, f: impl Future<Output = Result<T>>) -> Result<T> {
+        let _guard = QUEUE_LOCK.lock().await;
+        let open = Self::load(data_root)
+            .await?
+            .jobs
+            .iter()
+            .filter(|j| !j.is_finished())
+            .count();
+        anyhow::ensure!(
+            open == 0,
+            "{} jobs are queued or running, wait for them to finish or cancel them first",
+            open
+        );
+        f.await
+    }
+
+    /// Adds a pending job for `audio_file`, or returns the open job that already covers it.
+    /// A parked job is made pending again, a running one is left alone.
+    pub fn enqueue(&mut self, audio_file: &Path) -> QueuedJob {
+        if let Some(existing) = self
+            .jobs
+            .iter_mut()
+            .find(|j| j.audio_file == audio_file && !j.is_finished())
+        {
+            if existing.status == "parked" {
+                existing.status = "pending".into();
+                existing.updated_at = Utc::now().to_rfc3339();
+            }
+            return existing.clone();
+        }
+
+        let now = Utc::now().to_rfc3339();
+        let job = QueuedJob {
+            id: Uuid::new_v4().to_string(),
+            audio_file: audio_file.to_path_buf(),
+            status: "pending".into(),
+            attempts: 0,
+            last_error: None,
+            enqueued_at: now.clone(),
+            updated_at: now,
+            report: None,
+            force: Vec::new(),
+        };
+        self.jobs.push(job.clone());
+        job
+    }
+
+    /// Like `enqueue`, with `force` added to the job's forced steps. None if the session is being
+    /// processed right now, a second job would run it twice at the same time.
+    pub fn enqueue_forced(&mut self, audio_file: &Path, force: &[String]) -> Option<QueuedJob> {
+        let job = self.enqueue(audio_file);
+        if job.status == "running" {
+            return None;
+        }
+        let job = self.get_mut(&job.id)?;
+        for step in force {
+            if !job.force.contains(step) {
+                job.force.push(step.clone());
+            }
+        }
+        Some(job.clone())
+    }
+
+    pub fn get_mut(&mut self, id: &str) -> Option<&mut QueuedJob> {
+        self.jobs.iter_mut().find(|j| j.id == id)
+    }
+
+    pub fn set_status(&mut self, id: &str, status: &str, error: Option<String>) {
+        if let Some(job) = self.get_mut(id) {
+            job.status = status.into();
+            job.last_error = error;
+            job.updated_at = Utc::now().to_rfc3339();
+        }
+    }
+
+    /// Moves a pending job to running. False if someone else took it first.
+    pub fn claim(&mut self, id: &str) -> bool {
+        match self.get_mut(id) {
+            Some(job) if job.status == "pending" => {
+                job.status = "running".into();
+                job.attempts += 1;
+                job.updated_at = Utc::now().to_rfc3339();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Cancels a job that has not started yet. False if it is running or already finished.
+    pub fn cancel_waiting(&mut self, id: &str) -> bool {
+        match self.get_mut(id) {
+            Some(job) if job.status == "pending" || job.status == "parked" => {
+                job.status = "cancelled".into();
+                job.updated_at = Utc::now().to_rfc3339();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Keeps open jobs and the newest FINISHED_JOBS_KEPT finished ones.
+    fn prune_finished(&mut self) {
+        let mut finished: Vec<&str> = self
+            .jobs
+            .iter()
+            .filter(|j| j.is_finished())
+            .map(|j| j.updated_at.as_str())
+            .collect();
+        if finished.len() <= FINISHED_JOBS_KEPT {
+            return;
+        }
+        finished.sort_unstable_by(|a, b| b.cmp(a));
+        let oldest_kept = finished[FINISHED_JOBS_KEPT - 1].to_string();
+        self.jobs
+            .retain(|j| !j.is_finished() || j.updated_at >= oldest_kept);
+    }
+
+    pub fn with_status(&self, status: &str) -> Vec<QueuedJob> {
+        self.jobs.iter().filter(|j| j.status == status).cloned().collect()
+    }
+}
@@ -0,0 +1,166 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::fs;
+use walkdir::WalkDir;
+
+use crate::jobs::queue::JobQueue;
+use crate::processing::processing::{ProcessingFile, PurgedFile};
+use crate::store::settings::{RetentionSettings, Settings};
+
+const RUN_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Debug, Serialize, Default)]
+pub struct RetentionReport {
+    pub sessions_checked: usize,
+    pub purged: Vec<PurgedSessionFile>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PurgedSessionFile {
+    pub doc_id: String,
+    #[serde(flatten)]
+    pub file: PurgedFile,
+}
+
+/// Applies the retention policies once at startup and then daily.
+pub fn spawn(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let Ok(data_root) = app.path().app_data_dir().map(|d| d.join("data")) else {
+            println!("⚠ retention: app data dir not available");
+            return;
+        };
+
+        loop {
+            if let Err(err) = apply_retention(&data_root).await {
+                println!("⚠ retention: {}", err);
+            }
+            tokio::time::sleep(RUN_INTERVAL).await;
+        }
+    });
+}
+
+/// Removes recordings and transcripts of sessions older than the policies in settings.retention,
+/// counted from successful processing. Each removal is recorded in the session's processing.json.
+/// Sessions that are unprocessed, failed or queued keep all files; knowledge is never touched.
+pub async fn apply_retention(data_root: &Path) -> Result<RetentionReport> {
+    let policy = Settings::load_or_create(data_root).await?.retention;
+    let mut report = RetentionReport::default();
+    if policy.audio_days.is_none() && policy.transcript_days.is_none() {
+        return Ok(report);
+    }
+
+    let queue = JobQueue::snapshot(data_root).await?;
+    let now = Utc::now();
+
+    // data/YYYY/MM/recordNNNN/processing.json
+    for entry in WalkDir::new(data_root)
+        .min_depth(4)
+        .max_depth(4)
+        .into_iter()
+        .filter_map(Result::ok)
+    {
+        if entry.file_name() != "processing.json" {
+            continue;
+        }
+        let Some(record_dir) = entry.path().parent() else {
+            continue;
+        };
+
+        let mut processing = match ProcessingFile::load(record_dir).await {
+            Ok(processing) => processing,
+            Err(err) => {
+                println!("⚠ retention: skipping {} → {}", record_dir.display(), err);
+                continue;
+            }
+        };
+        report.sessions_checked += 1;
+
+        let Some(finished) = processing
+            .finished_at
+            .as_deref()
+            .and_then(|f| DateTime::parse_from_rfc3339(f).ok())
+        else {
+            continue;
+        };
+        if processing.has_errors() {
+            continue;
+        }
+        let source = processing.source_path(record_dir);
+        let queued = queue.jobs.iter().any(|j| {
+            j.audio_file == source && matches!(j.status.as_str(), "pending" | "running" | "parked")
+        });
+        if queued {
+            continue;
+        }
+
+        let age_days = (now - finished.with_timezone(&Utc)).num_days();
+        let mut purged = Vec::new();
+        for (path, kind, days) in due_files(&processing, record_dir, &policy, age_days) {
+            if !path.exists() {
+                continue;
+            }
+            if let Err(err) = fs::remove_file(&path).await {
+                println!("⚠ retention: cannot remove {} → {}", path.display(), err);
+                continue;
+            }
+            println!("▶ retention: removed {} ({} after {} days)", path.display(), kind, days);
+            purged.push(PurgedFile {
+                file: path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+                kind: kind.into(),
+                purged_at: now.to_rfc3339(),
+                policy: format!("{} after {} days", kind, days),
+            });
+        }
+
+        if purged.is_empty() {
+            continue;
+        }
+        processing.purged.extend(purged.iter().cloned());
+        processing.save(record_dir).await?;
+        report
+            .purged
+            .extend(purged.into_iter().map(|file| PurgedSessionFile {
+                doc_id: processing.doc_id.clone(),
+                file,
+            }));
+    }
+
+    println!(
+        "▶ retention: {} files removed in {} sessions",
+        report.purged.len(),
+        report.sessions_checked
+    );
+    Ok(report)
+}
+
+/// Recording of audio sessions; text.txt, and for text sessions the stored document as their transcript.
+fn due_files(
+    processing: &ProcessingFile,
+    record_dir: &Path,
+    policy: &RetentionSettings,
+    age_days: i64,
+) -> Vec<(PathBuf, &'static str, u32)> {
+    let source = (!processing.audio_file.is_empty()).then(|| processing.source_path(record_dir));
+    let mut due = Vec::new();
+
+    if let Some(days) = policy.audio_days.filter(|d| age_days >= *d as i64) {
+        if let Some(source) = source.clone().filter(|_| processing.has_audio()) {
+            due.push((source, "audio", days));
+        }
+    }
+    if let Some(days) = policy.transcript_days.filter(|d| age_days >= *d as i64) {
+        due.push((record_dir.join("text.txt"), "transcript", days));
+        if let Some(source) = source.filter(|_| !processing.has_audio()) {
+            due.push((source, "transcript", days));
+        }
+    }
+
+    due
+}
@@ -0,0 +1,162 @@
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Manager};
+use tokio::fs;
+
+use crate::jobs::queue::JobQueue;
+use crate::jobs::worker;
+use crate::paths::next_record_path;
+use crate::processing::ingest::{ingest_text_file, text_kind};
+use crate::store::settings::{Settings, WatchFolderSettings};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+pub(crate) const AUDIO_EXTENSIONS: [&str; 6] = ["wav", "mp3", "m4a", "ogg", "webm", "flac"];
+const IMPORTED_DIR: &str = "imported";
+const MARK_SUFFIX: &str = "via-imported";
+
+/// Size and mtime of a file at the last poll. A file is imported once both stayed the same
+/// for a full interval, so half-synced files are not picked up.
+#[derive(PartialEq)]
+struct Seen {
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+/// Starts polling the watch folder from settings.json. Changes to the setting apply at the next poll.
+pub fn spawn(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let Ok(data_root) = app.path().app_data_dir().map(|d| d.join("data")) else {
+            println!("⚠ watch folder: app data dir not available");
+            return;
+        };
+
+        let mut seen: HashMap<PathBuf, Seen> = HashMap::new();
+
+        loop {
+            if let Err(err) = poll(&data_root, &mut seen).await {
+                println!("⚠ watch folder: {}", err);
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    });
+}
+
+async fn poll(data_root: &Path, seen: &mut HashMap<PathBuf, Seen>) -> Result<()> {
+    let settings = Settings::load_or_create(data_root).await?;
+    let Some(folder) = settings.watch_folder.path.clone() else {
+        seen.clear();
+        return Ok(());
+    };
+    if !folder.is_dir() {
+        return Ok(());
+    }
+
+    let mut current = HashMap::new();
+    let mut entries = fs::read_dir(&folder)
+        .await
+        .with_context(|| format!("failed to read {}", folder.display()))?;
+
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        if !is_candidate(&path) {
+            continue;
+        }
+        let meta = entry.metadata().await?;
+        if !meta.is_file() {
+            continue;
+        }
+
+        let now = Seen {
+            len: meta.len(),
+            modified: meta.modified().ok(),
+        };
+
+        if seen.get(&path) == Some(&now) && now.len > 0 {
+            match import(data_root, &path, &settings.watch_folder).await {
+                Ok(target) => println!("▶ watch folder: imported {} → {}", path.display(), target.display()),
+                Err(err) => println!("⚠ watch folder: import of {} failed → {}", path.display(), err),
+            }
+            continue;
+        }
+        current.insert(path, now);
+    }
+
+    *seen = current;
+    Ok(())
+}
+
+fn is_candidate(path: &Path) -> bool {
+    let Some(name) = path.file_name().map(|n| n.to_string_lossy().to_string()) else {
+        return false;
+    };
+    // hidden files are usually temp files of sync clients
+    if name.starts_with('.') {
+        return false;
+    }
+    if mark_path(path).exists() {
+        return false;
+    }
+
+    text_kind(path).is_some()
+        || path
+            .extension()
+            .map(|e| e.to_string_lossy().to_ascii_lowercase())
+            .is_some_and(|e| AUDIO_EXTENSIONS.contains(&e.as_str()))
+}
+
+fn mark_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}", MARK_SUFFIX));
+    path.with_file_name(name)
+}
+
+/// Copies the file to data/YYYY/MM/recordNNNN.{ext}, queues it and moves or marks the source.
+/// Text files become text sessions right away, see `ingest_text_file`.
+async fn import(data_root: &Path, source: &Path, settings: &WatchFolderSettings) -> Result<PathBuf> {
+    let target = if text_kind(source).is_some() {
+        ingest_text_file(data_root, source).await?
+    } else {
+        let extension = source
+            .extension()
+            .map(|e| e.to_string_lossy().to_ascii_lowercase())
+            .context("file has no extension")?;
+
+        let target = next_record_path(data_root, &extension)?;
+        fs::copy(source, &target)
+            .await
+            .with_context(|| format!("failed to copy to {}", target.display()))?;
+        target
+    };
+
+    // queued before the source is retired: a crash in between imports it twice instead of losing it
+    JobQueue::update(data_root, |q| q.enqueue(&target)).await?;
+    worker::wake();
+
+    retire_source(source, settings).await?;
+
+    Ok(target)
+}
+
+async fn retire_source(source: &Path, settings: &WatchFolderSettings) -> Result<()> {
+    if settings.after_import == "mark" {
+        fs::write(mark_path(source), b"").await?;
+        return Ok(());
+    }
+
+    let folder = source.parent().context("source has no parent")?;
+    let imported = folder.join(IMPORTED_DIR);
+    fs::create_dir_all(&imported).await?;
+
+    let mut target = imported.join(source.file_name().context("source has no name")?);
+    if target.exists() {
+        let stem = source.file_stem().unwrap_or_default().to_string_lossy();
+        let ext = source.extension().unwrap_or_default().to_string_lossy();
+        target = imported.join(format!("{}-{}.{}", stem, chrono::Utc::now().timestamp(), ext));
+    }
+
+    fs::rename(source, &target)
+        .await
+        .with_context(|| format!("failed to move {} to {}", source.display(), target.display()))
+}
@@ -0,0 +1,261 @@
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::sync::Notify;
+use tokio_util::sync::CancellationToken;
+
+use crate::jobs::queue::{JobQueue, QueuedJob};
+use crate::paths::record_dir_from_audio;
+use crate::pipeline::error::PipelineError;
+use crate::pipeline::report::PipelineReport;
+use crate::pipeline::context::RecordContext;
+use crate::processing::document::process_document;
+use crate::processing::processing::ProcessingFile;
+use crate::processing::progress::JobProgressEmitter;
+use crate::processing::state_global::update_global_state;
+use crate::redaction::redactor::Redactor;
+use crate::state::AppState;
+use crate::usage::report::ensure_within_budget;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+static WAKE: Notify = Notify::const_new();
+
+/// Lets the worker look at the queue now instead of at the next poll.
+pub fn wake() {
+    WAKE.notify_one();
+}
+
+/// How a job run ended.
+pub enum JobOutcome {
+    /// Finished, possibly with failed non-critical steps, see the report.
+    Done(PipelineReport),
+    /// A critical step failed.
+    Failed(PipelineError),
+    /// Offline, the job stays in the queue until the network is back.
+    Parked(String),
+    /// Stopped by `cancel_processing`, the global stores were not touched.
+    Cancelled,
+}
+
+/// Starts the background worker that resumes queued and parked jobs.
+pub fn spawn(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let Ok(data_root) = app.path().app_data_dir().map(|d| d.join("data")) else {
+            println!("⚠ queue worker: app data dir not available");
+            return;
+        };
+
+        // a job still marked running was interrupted by an app exit
+        if let Err(err) = JobQueue::update(&data_root, |q| {
+            for job in q.jobs.iter_mut().filter(|j| j.status == "running") {
+                job.status = "pending".into();
+            }
+        })
+            .await
+        {
+            println!("⚠ queue worker: failed to recover jobs → {}", err);
+        }
+
+        loop {
+            if let Err(err) = drain(&app, &data_root).await {
+                println!("⚠ queue worker: {}", err);
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(POLL_INTERVAL) => {}
+                _ = WAKE.notified() => {}
+            }
+        }
+    });
+}
+
+async fn drain(app: &AppHandle, data_root: &Path) -> anyhow::Result<()> {
+    let queue = JobQueue::snapshot(data_root).await?;
+
+    let parked = queue.with_status("parked");
+    if !parked.is_empty() && is_online().await {
+        println!("▶ queue worker: back online, resuming {} parked jobs", parked.len());
+        JobQueue::update(data_root, |q| {
+            for job in q.jobs.iter_mut().filter(|j| j.status == "parked") {
+                job.status = "pending".into();
+            }
+        })
+            .await?;
+    }
+
+    // claimed jobs run in their own tasks, the job manager bounds how many process at once
+    let pending = JobQueue::snapshot(data_root).await?.with_status("pending");
+    for job in pending {
+        if !JobQueue::update(data_root, |q| q.claim(&job.id)).await? {
+            continue;
+        }
+        println!("▶ queue worker: starting job {} ({})", job.id, job.audio_file.display());
+
+        let app = app.clone();
+        let data_root = data_root.to_path_buf();
+        tauri::async_runtime::spawn(async move {
+            if let Err(err) = execute_job(&app, &data_root, &job).await {
+                println!("⚠ queue worker: job {} failed → {}", job.id, err);
+            }
+        });
+    }
+
+    Ok(())
+}
+
+/// Cheap reachability check of the API host. Any HTTP answer, even 401, counts as online.
+pub async fn is_online() -> bool {
+    let client = match reqwest::Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+    {
+        Ok(c) => c,
+        Err(_) => return false,
+    };
+
+    client
+        .head("https://api.openai.com/v1/models")
+        .send()
+        .await
+        .is_ok()
+}
+
+/// Processes a claimed (running) job and writes its final state back to the queue.
+/// Waits for a free slot of the job manager first, the job can be cancelled while waiting.
+pub async fn execute_job(
+    app: &AppHandle,
+    data_root: &Path,
+    job: &QueuedJob,
+) -> Result<JobOutcome, String> {
+    let state = app.state::<AppState>();
+    let cancel = state.jobs.register(&job.id);
+
+    let permit = tokio::select! {
+        permit = state.jobs.acquire() => Some(permit),
+        _ = cancel.cancelled() => None,
+    };
+    let result = match permit {
+        Some(_permit) => process_job(app, job, cancel).await,
+        None => Ok(JobOutcome::Cancelled),
+    };
+    state.jobs.remove(&job.id);
+
+    let (status, error, report) = match &result {
+        Ok(JobOutcome::Done(report)) => ("done", None, Some(report.clone())),
+        Ok(JobOutcome::Failed(err)) => ("failed", Some(err.to_string()), err.report().cloned()),
+        Ok(JobOutcome::Parked(reason)) => ("parked", Some(reason.clone()), None),
+        Ok(JobOutcome::Cancelled) => ("cancelled", None, None),
+        Err(err) => ("failed", Some(err.clone()), None),
+    };
+
+    JobQueue::update(data_root, |q| {
+        q.set_status(&job.id, status, error);
+        if let Some(job) = q.get_mut(&job.id) {
+            job.report = report;
+        }
+    })
+        .await
+        .map_err(|e| e.to_string())?;
+
+    result
+}
+
+async fn process_job(
+    app: &AppHandle,
+    job: &QueuedJob,
+    cancel: CancellationToken,
+) -> Result<JobOutcome, String> {
+    let audio_path = &job.audio_file;
+    let base_dir = audio_path
+        .parent()
+        .ok_or("audio file has no parent directory")?
+        .to_path_buf();
+
+    let mut ctx = RecordContext {
+        base_dir: base_dir.clone(),
+        audio_file: audio_path.to_path_buf(),
+        cancel,
+        ..Default::default()
+    };
+
+    // processing.json is created up front, so every event can name the document
+    let record_dir = record_dir_from_audio(&ctx.base_dir, &ctx.audio_file)
+        .await
+        .map_err(|e| e.to_string())?;
+    let processing = ProcessingFile::load_or_create(&record_dir, audio_path)
+        .await
+        .map_err(|e| e.to_string())?;
+    processing
+        .save(&record_dir)
+        .await
+        .map_err(|e| e.to_string())?;
+    ctx.progress = Some(Arc::new(JobProgressEmitter::new(
+        app.clone(),
+        &job.id,
+        Some(processing.doc_id),
+    )));
+
+    let data_root = ctx.data_root().map_err(|e| e.to_string())?;
+    let redactor = Redactor::load(&data_root)
+        .await
+        .map_err(|e| e.to_string())?;
+    ctx.redactor = Some(Arc::new(redactor));
+
+    ensure_within_budget(&data_root)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if !is_online().await {
+        ctx.emit("queued", "Offline – Aufnahme wird später verarbeitet", 100);
+        return Ok(JobOutcome::Parked("network unavailable".into()));
+    }
+
+    ctx.emit("start", "Verarbeitung gestartet", 5);
+
+    let result = process_document(&ctx.phase(5, 80), &job.force).await;
+    if ctx.is_cancelled() {
+        ctx.emit("cancelled", "Verarbeitung abgebrochen", 100);
+        return Ok(JobOutcome::Cancelled);
+    }
+
+    let processing = ProcessingFile::load(&record_dir)
+        .await
+        .map_err(|e| e.to_string())?;
+    let offline_steps = processing.network_failures();
+    if !offline_steps.is_empty() {
+        ctx.emit("queued", "Netzwerkfehler – Aufnahme wird später erneut verarbeitet", 100);
+        return Ok(JobOutcome::Parked(format!(
+            "network error in {}",
+            offline_steps.join(", ")
+        )));
+    }
+
+    let mut report = match result {
+        Ok(report) => report,
+        Err(err) => {
+            ctx.emit("failed", &err.to_string(), 100);
+            return Ok(JobOutcome::Failed(err));
+        }
+    };
+
+    // last point to stop, after this the global stores are being written
+    if ctx.is_cancelled() {
+        ctx.emit("cancelled", "Verarbeitung abgebrochen", 100);
+        return Ok(JobOutcome::Cancelled);
+    }
+
+    // a cancel from here on must not leave signals and evidence updated but knowledge not
+    let global_ctx = RecordContext {
+        cancel: CancellationToken::new(),
+        ..ctx.phase(80, 99)
+    };
+    report.extend(update_global_state(&global_ctx).await?);
+    if report.is_partial() {
+        ctx.emit("done", &format!("Fertig, {}", report.partial_summary()), 100);
+    } else {
+        ctx.emit("done", "Fertig", 100);
+    }
+
+    Ok(JobOutcome::Done(report))
+}
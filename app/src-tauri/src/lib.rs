@@ -7,6 +7,13 @@ mod commands;
 mod resolvers;
 mod state;
 mod recording;
+mod openai;
+mod usage;
+mod prompts;
+mod jobs;
+mod redaction;
+mod storage;
+mod backup;
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
   tauri::Builder::default()
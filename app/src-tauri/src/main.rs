@@ -10,6 +10,13 @@ pub mod processing;
 pub mod commands;
 pub mod resolvers;
 pub mod state;
+pub mod openai;
+pub mod usage;
+pub mod prompts;
+pub mod jobs;
+pub mod redaction;
+pub mod storage;
+pub mod backup;
 use crate::agents::agent::Agent;
 use crate::commands::sessions::list_sessions;
 use crate::state::AppState;
@@ -22,14 +29,36 @@ use commands::todo_confirm::confirm_todo;
 use commands::todo_ignore::ignore_todo;
 use commands::todos::list_todos;
 use commands::process_recording::process_recording;
+use commands::cancel_processing::cancel_processing;
+use commands::jobs::{get_job, list_jobs};
+use commands::reprocess::reprocess_sessions;
+use commands::rebuild_global_state::rebuild_global_state;
+use commands::storage::migrate_storage;
+use commands::knowledge_history::{get_record_history, restore_record, undo_change};
+use commands::check_data::check_data;
+use commands::retention::apply_retention;
+use commands::trash::{delete_session, empty_trash, list_trash, restore_session};
+use commands::backup::{export_backup, import_backup};
+use commands::snapshots::{create_snapshot, diff_knowledge, list_snapshots, restore_snapshot};
+use commands::ingest_text::ingest_text;
+use commands::prompts::{ab_test_prompt, list_prompts};
+use commands::settings::{get_settings, update_settings};
+use commands::usage_report::get_usage_report;
 
 
 fn main() {
     dotenvy::dotenv().ok();
     tauri::Builder::default()
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .setup(|app| {
+            jobs::worker::spawn(app.handle().clone());
+            jobs::watcher::spawn(app.handle().clone());
+            jobs::retention::spawn(app.handle().clone());
+            Ok(())
+        })
         .manage(AppState {
-            recorder: Mutex::new(Recording::new())
+            recorder: Mutex::new(Recording::new()),
+            jobs: Default::default(),
         })
         .invoke_handler(tauri::generate_handler![
             recorder_status,
@@ -37,11 +66,38 @@ fn main() {
             start_recording,
             stop_recording,
             process_recording,
+            cancel_processing,
+            list_jobs,
+            get_job,
+            reprocess_sessions,
+            rebuild_global_state,
+            migrate_storage,
+            ingest_text,
             list_todos,
             confirm_todo,
             ignore_todo,
+            get_record_history,
+            undo_change,
+            restore_record,
+            create_snapshot,
+            list_snapshots,
+            diff_knowledge,
+            restore_snapshot,
+            export_backup,
+            import_backup,
+            check_data,
+            delete_session,
+            list_trash,
+            restore_session,
+            empty_trash,
+            apply_retention,
             get_knowledge_overview,
             get_knowledge_graph,
+            get_usage_report,
+            get_settings,
+            update_settings,
+            list_prompts,
+            ab_test_prompt,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,126 @@
+use anyhow::{Context, Result};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+use crate::openai::retry::{send_with_retry, RetryPolicy};
+use crate::pipeline::context::RecordContext;
+use crate::redaction::redactor::{Redaction, Redactor};
+use crate::usage::model::UsageEntry;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChatMessage {
+    pub role: String,
+    pub content: String,
+}
+
+impl ChatMessage {
+    pub fn system(content: impl Into<String>) -> Self {
+        Self { role: "system".into(), content: content.into() }
+    }
+
+    pub fn user(content: impl Into<String>) -> Self {
+        Self { role: "user".into(), content: content.into() }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChatRequest {
+    pub model: String,
+    pub messages: Vec<ChatMessage>,
+    pub temperature: f32,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatResponse {
+    choices: Vec<ChatChoice>,
+    #[serde(default)]
+    usage: Option<ChatUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatChoice {
+    message: ChatMessage,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatUsage {
+    #[serde(default)]
+    prompt_tokens: u64,
+    #[serde(default)]
+    completion_tokens: u64,
+}
+
+/// Sends a chat completion and returns the content of the first choice ("[]" if none).
+/// Sensitive spans are replaced by placeholders before sending and restored in the answer.
+/// Retries per the default policy; token usage is recorded on the context for the running step.
+pub async fn chat_completion(
+    ctx: &RecordContext,
+    step: &str,
+    api_key: &str,
+    request: &ChatRequest,
+) -> Result<String> {
+    let mut redaction = Redaction::default();
+    let messages: Vec<ChatMessage> = {
+        let fallback;
+        let redactor = match &ctx.redactor {
+            Some(r) => r.as_ref(),
+            None => {
+                fallback = Redactor::defaults();
+                &fallback
+            }
+        };
+        request
+            .messages
+            .iter()
+            .map(|m| ChatMessage {
+                role: m.role.clone(),
+                content: redactor.redact(&m.content, &mut redaction),
+            })
+            .collect()
+    };
+    if !redaction.is_empty() {
+        println!("▶ {} redacted {} sensitive values", step, redaction.len());
+    }
+    let redacted = ChatRequest {
+        model: request.model.clone(),
+        messages,
+        temperature: request.temperature,
+    };
+
+    let client = Client::builder()
+        .timeout(std::time::Duration::from_secs(30))
+        .user_agent(format!("via/0.1 {}", step.to_lowercase()))
+        .build()
+        .context("failed to build reqwest client")?;
+
+    let response = send_with_retry(ctx, step, &RetryPolicy::default(), || {
+        Ok(client
+            .post("https://api.openai.com/v1/chat/completions")
+            .bearer_auth(api_key)
+            .json(&redacted))
+    })
+        .await?;
+
+    let response: ChatResponse = response
+        .json()
+        .await
+        .context("invalid OpenAI response")?;
+
+    ctx.record_model(&request.model);
+    if let Some(usage) = &response.usage {
+        ctx.record_usage(UsageEntry::chat(
+            step,
+            &request.model,
+            usage.prompt_tokens,
+            usage.completion_tokens,
+        ));
+    }
+
+    let content = response
+        .choices
+        .first()
+        .map(|c| c.message.content.clone())
+        .unwrap_or_else(|| "[]".into());
+
+    Ok(redaction.restore_json(&content))
+}
@@ -0,0 +1,233 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use reqwest::{RequestBuilder, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::pipeline::context::RecordContext;
+
+/// How often and how patiently outbound API calls are retried.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// Upper bound for a server supplied Retry-After, so a bad header cannot stall a job for hours.
+    pub max_retry_after: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(20),
+            max_retry_after: Duration::from_secs(60),
+        }
+    }
+}
+
+/// One try of an outbound request, stored in the step's processing entry.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RequestAttempt {
+    pub attempt: u32,
+    /// ok | network_error | rate_limited | server_error | client_error | cancelled
+    pub outcome: String,
+    pub status: Option<u16>,
+    pub error: Option<String>,
+    /// Wait before the next attempt, None if no retry followed.
+    pub retry_in_ms: Option<u64>,
+    pub at: String,
+}
+
+impl RequestAttempt {
+    pub fn is_network_error(&self) -> bool {
+        self.outcome == "network_error"
+    }
+}
+
+enum Outcome {
+    Ok,
+    Retryable { kind: &'static str, retry_after: Option<Duration> },
+    Fatal { kind: &'static str },
+}
+
+impl RetryPolicy {
+    /// Exponential backoff with equal jitter: half the window fixed, half random.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let exp = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_delay);
+        let half = exp / 2;
+        half + Duration::from_millis(fastrand::u64(0..=half.as_millis() as u64))
+    }
+
+    fn delay_for(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        match retry_after {
+            Some(d) => d.min(self.max_retry_after),
+            None => self.backoff(attempt),
+        }
+    }
+}
+
+fn classify_status(status: StatusCode, body: &str) -> Outcome {
+    if status.is_success() {
+        return Outcome::Ok;
+    }
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        // OpenAI also answers 429 when the account is out of credit, waiting will not help
+        if body.contains("insufficient_quota") {
+            return Outcome::Fatal { kind: "client_error" };
+        }
+        return Outcome::Retryable { kind: "rate_limited", retry_after: None };
+    }
+    if status.is_server_error() || status == StatusCode::REQUEST_TIMEOUT {
+        return Outcome::Retryable { kind: "server_error", retry_after: None };
+    }
+    Outcome::Fatal { kind: "client_error" }
+}
+
+fn classify_error(err: &reqwest::Error) -> Outcome {
+    if err.is_timeout() || err.is_connect() || err.is_request() || err.is_body() {
+        Outcome::Retryable { kind: "network_error", retry_after: None }
+    } else {
+        // builder, redirect or decode errors, trying again later fails the same way
+        Outcome::Fatal { kind: "client_error" }
+    }
+}
+
+/// Reads `retry-after-ms` or `Retry-After` (seconds or HTTP date).
+fn retry_after(response: &Response) -> Option<Duration> {
+    let headers = response.headers();
+
+    if let Some(ms) = headers
+        .get("retry-after-ms")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<f64>().ok())
+    {
+        return Some(Duration::from_millis(ms.max(0.0) as u64));
+    }
+
+    let value = headers.get(reqwest::header::RETRY_AFTER)?.to_str().ok()?.trim();
+
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let at = DateTime::parse_from_rfc2822(value).ok()?;
+    let wait = at.with_timezone(&Utc) - Utc::now();
+    Some(wait.to_std().unwrap_or(Duration::ZERO))
+}
+
+/// Sends the request built by `build` until it succeeds, fails fatally or the policy gives up.
+/// `build` is called once per attempt because request bodies are consumed on send.
+/// Every attempt is recorded on the context for the running step.
+/// Cancelling the context drops the in-flight request and stops waiting for the next attempt.
+pub async fn send_with_retry<F>(
+    ctx: &RecordContext,
+    step: &str,
+    policy: &RetryPolicy,
+    build: F,
+) -> Result<Response>
+where
+    F: Fn() -> Result<RequestBuilder>,
+{
+    let mut attempt = 0;
+
+    loop {
+        attempt += 1;
+
+        let request = build()?;
+        let sent = tokio::select! {
+            sent = request.send() => sent,
+            _ = ctx.cancel.cancelled() => {
+                record_cancelled(ctx, attempt);
+                println!("▶ {} cancelled during attempt {}", step, attempt);
+                anyhow::bail!("request cancelled");
+            }
+        };
+
+        let (outcome, status, error, response) = match sent {
+            Ok(response) => {
+                let status = response.status();
+                if status.is_success() {
+                    (Outcome::Ok, Some(status.as_u16()), None, Some(response))
+                } else {
+                    let header_delay = retry_after(&response);
+                    let body = response.text().await.unwrap_or_default();
+                    let outcome = match classify_status(status, &body) {
+                        Outcome::Retryable { kind, .. } => Outcome::Retryable {
+                            kind,
+                            retry_after: header_delay,
+                        },
+                        other => other,
+                    };
+                    (outcome, Some(status.as_u16()), Some(body), None)
+                }
+            }
+            Err(err) => (classify_error(&err), None, Some(err.to_string()), None),
+        };
+
+        let (kind, retry_in) = match &outcome {
+            Outcome::Ok => ("ok", None),
+            Outcome::Retryable { kind, retry_after } if attempt < policy.max_attempts => {
+                (*kind, Some(policy.delay_for(attempt, *retry_after)))
+            }
+            Outcome::Retryable { kind, .. } => (*kind, None),
+            Outcome::Fatal { kind } => (*kind, None),
+        };
+
+        ctx.record_attempt(RequestAttempt {
+            attempt,
+            outcome: kind.into(),
+            status,
+            error: error.clone(),
+            retry_in_ms: retry_in.map(|d| d.as_millis() as u64),
+            at: Utc::now().to_rfc3339(),
+        });
+
+        if let Some(response) = response {
+            return Ok(response);
+        }
+
+        let error = error.unwrap_or_default();
+        match retry_in {
+            Some(delay) => {
+                println!(
+                    "▶ {} attempt {}/{} failed ({}), retrying in {} ms",
+                    step,
+                    attempt,
+                    policy.max_attempts,
+                    kind,
+                    delay.as_millis()
+                );
+                tokio::select! {
+                    _ = tokio::time::sleep(delay) => {}
+                    _ = ctx.cancel.cancelled() => {
+                        println!("▶ {} cancelled while waiting for attempt {}", step, attempt + 1);
+                        anyhow::bail!("request cancelled");
+                    }
+                }
+            }
+            None => {
+                println!("▶ {} giving up after attempt {} ({})", step, attempt, kind);
+                match status {
+                    Some(_) => anyhow::bail!("OpenAI error: {}", error),
+                    None => anyhow::bail!("request failed: {}", error),
+                }
+            }
+        }
+    }
+}
+
+fn record_cancelled(ctx: &RecordContext, attempt: u32) {
+    ctx.record_attempt(RequestAttempt {
+        attempt,
+        outcome: "cancelled".into(),
+        status: None,
+        error: None,
+        retry_in_ms: None,
+        at: Utc::now().to_rfc3339(),
+    });
+}
@@ -0,0 +1,98 @@
+use anyhow::{Context, Result};
+use reqwest::{Client, multipart::{Form, Part}};
+use serde::Deserialize;
+use std::io::Cursor;
+
+use crate::openai::retry::{send_with_retry, RetryPolicy};
+use crate::pipeline::context::RecordContext;
+use crate::usage::model::UsageEntry;
+
+const WHISPER_MODEL: &str = "whisper-1";
+
+/// Assumed bitrate of compressed recordings (128 kbit/s), when neither Whisper nor the file tells the length.
+const COMPRESSED_BYTES_PER_SECOND: f64 = 16_000.0;
+
+/// verbose_json, it carries the length of the audio Whisper billed.
+#[derive(Debug, Deserialize)]
+struct WhisperResponse {
+    text: String,
+    #[serde(default)]
+    duration: Option<f64>,
+}
+
+/// Length of a WAV recording in seconds, None if the bytes are no readable WAV.
+fn wav_duration_seconds(bytes: &[u8]) -> Option<f64> {
+    let reader = hound::WavReader::new(Cursor::new(bytes)).ok()?;
+    let spec = reader.spec();
+    if spec.sample_rate == 0 {
+        return None;
+    }
+    Some(reader.duration() as f64 / spec.sample_rate as f64)
+}
+
+/// Upload name and mime type by file extension, everything unknown is sent as WAV.
+fn audio_format(extension: &str) -> (&'static str, &'static str) {
+    match extension.to_ascii_lowercase().as_str() {
+        "mp3" => ("audio.mp3", "audio/mpeg"),
+        "m4a" => ("audio.m4a", "audio/mp4"),
+        "ogg" => ("audio.ogg", "audio/ogg"),
+        "webm" => ("audio.webm", "audio/webm"),
+        "flac" => ("audio.flac", "audio/flac"),
+        _ => ("audio.wav", "audio/wav"),
+    }
+}
+
+/// Transcribes ctx.audio_file's content (with retries) and records the billed audio seconds
+/// on the context: Whisper's reported duration, else the WAV header, else an estimate from the file size.
+pub async fn transcribe(
+    ctx: &RecordContext,
+    step: &str,
+    api_key: &str,
+    audio: Vec<u8>,
+    language: &str,
+) -> Result<String> {
+    let local_seconds = wav_duration_seconds(&audio)
+        .unwrap_or(audio.len() as f64 / COMPRESSED_BYTES_PER_SECOND);
+    let (file_name, mime) = audio_format(
+        &ctx.audio_file
+            .extension()
+            .map(|e| e.to_string_lossy().to_string())
+            .unwrap_or_default(),
+    );
+
+    let client = Client::builder()
+        .timeout(std::time::Duration::from_secs(120))
+        .user_agent(format!("via/0.1 {}", step))
+        .build()
+        .context("failed to build reqwest client")?;
+
+    let response = send_with_retry(ctx, step, &RetryPolicy::default(), || {
+        // multipart forms are consumed on send, so every attempt builds a fresh one
+        let part = Part::bytes(audio.clone())
+            .file_name(file_name)
+            .mime_str(mime)?;
+
+        let form = Form::new()
+            .part("file", part)
+            .text("language", language.to_string())
+            .text("model", WHISPER_MODEL)
+            .text("response_format", "verbose_json");
+
+        Ok(client
+            .post("https://api.openai.com/v1/audio/transcriptions")
+            .bearer_auth(api_key)
+            .multipart(form))
+    })
+        .await?;
+
+    let whisper: WhisperResponse = response
+        .json()
+        .await
+        .context("invalid whisper response")?;
+
+    ctx.record_model(WHISPER_MODEL);
+    let audio_seconds = whisper.duration.filter(|d| *d > 0.0).unwrap_or(local_seconds);
+    ctx.record_usage(UsageEntry::transcription(step, WHISPER_MODEL, audio_seconds));
+
+    Ok(whisper.text)
+}
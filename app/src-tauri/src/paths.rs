@@ -19,27 +19,45 @@ pub async fn record_dir_from_audio(base_dir: &Path, audio_file: &Path) -> Result
     Ok(record_dir)
 }
 pub fn next_recording_path(app: &AppHandle) -> PathBuf {
-    let now = Local::now();
-    let year = now.format("%Y").to_string();
-    let month = now.format("%m").to_string();
-
-    let mut base = app
+    let data_root = app
         .path()
         .app_data_dir()
-        .expect("app data dir not available");
+        .expect("app data dir not available")
+        .join("data");
+
+    next_record_path(&data_root, "wav").expect("failed to create directories")
+}
 
-    base.push("data");
-    base.push(year);
-    base.push(month);
+/// Next free data/YYYY/MM/recordNNNN.{extension} of the current month.
+pub fn next_record_path(data_root: &Path, extension: &str) -> Result<PathBuf> {
+    let now = Local::now();
+    let base = data_root
+        .join(now.format("%Y").to_string())
+        .join(now.format("%m").to_string());
+
+    fs::create_dir_all(&base).context("failed to create month directory")?;
+
+    let stem = next_free_stem(&base)?;
+    Ok(base.join(format!("{}.{}", stem, extension)))
+}
 
-    fs::create_dir_all(&base).expect("failed to create directories");
+/// Next recordNNNN stem in a month directory.
+/// A number counts as taken if any file or the record dir already uses the stem.
+pub fn next_free_stem(month_dir: &Path) -> Result<String> {
+    let taken: Vec<String> = fs::read_dir(month_dir)?
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            e.path()
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+        })
+        .collect();
 
     let mut index = 1;
     loop {
-        let filename = format!("record{:04}.wav", index);
-        let candidate = base.join(&filename);
-        if !candidate.exists() {
-            return candidate;
+        let stem = format!("record{:04}", index);
+        if !taken.contains(&stem) {
+            return Ok(stem);
         }
         index += 1;
     }
@@ -1,12 +1,25 @@
 use std::path::PathBuf;
-use std::sync::Arc;
-use crate::processing::progress::ProgressEmitter;
+use anyhow::{Context, Result};
+use std::sync::{Arc, Mutex};
+use tokio_util::sync::CancellationToken;
+use crate::openai::retry::RequestAttempt;
+use crate::processing::model::StepOutput;
+use crate::processing::progress::{PhaseProgress, ProgressEmitter};
+use crate::redaction::redactor::Redactor;
+use crate::usage::model::UsageEntry;
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct RecordContext {
     pub base_dir: PathBuf,
     pub audio_file: PathBuf,
     pub progress: Option<Arc<dyn ProgressEmitter>>,
+    /// Collects what the currently running step reports (usage, request attempts).
+    /// The pipeline drains it into processing.json after every step.
+    pub output: Arc<Mutex<StepOutput>>,
+    /// Applied to every chat request, the default rules are used if not set.
+    pub redactor: Option<Arc<Redactor>>,
+    /// Cancelled by `cancel_processing`, aborts in-flight requests and pending steps.
+    pub cancel: CancellationToken,
 }
 
 impl RecordContext {
@@ -15,4 +28,71 @@ impl RecordContext {
             p.emit(stage, message, percent);
         }
     }
+
+    pub fn emit_step(&self, step: &str, status: &str, percent: u8) {
+        if let Some(p) = &self.progress {
+            p.emit_step(step, status, percent);
+        }
+    }
+
+    /// Same record, progress mapped into `from..to` of the job.
+    pub fn phase(&self, from: u8, to: u8) -> RecordContext {
+        RecordContext {
+            progress: self.progress.clone().map(|inner| {
+                Arc::new(PhaseProgress::new(inner, from, to)) as Arc<dyn ProgressEmitter>
+            }),
+            ..self.clone()
+        }
+    }
+
+    /// data/YYYY/MM → data
+    pub fn data_root(&self) -> Result<PathBuf> {
+        Ok(self
+            .base_dir
+            .parent()
+            .and_then(|p| p.parent())
+            .context("base_dir is not data/YYYY/MM")?
+            .to_path_buf())
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    /// Called right before a step writes its results, a cancelled run leaves the files as they were.
+    pub fn ensure_not_cancelled(&self) -> Result<()> {
+        if self.is_cancelled() {
+            anyhow::bail!("processing cancelled");
+        }
+        Ok(())
+    }
+
+    pub fn record_usage(&self, entry: UsageEntry) {
+        self.output.lock().unwrap().usage.push(entry);
+    }
+
+    pub fn record_count(&self, name: &str, count: usize) {
+        self.output.lock().unwrap().counts.insert(name.into(), count);
+    }
+
+    pub fn record_model(&self, model: &str) {
+        self.output.lock().unwrap().model = Some(model.into());
+    }
+
+    pub fn record_prompt(&self, prompt_id: &str) {
+        self.output.lock().unwrap().prompt_version = Some(prompt_id.into());
+    }
+
+    pub fn record_attempt(&self, attempt: RequestAttempt) {
+        self.output.lock().unwrap().attempts.push(attempt);
+    }
+
+    pub fn take_output(&self) -> Option<StepOutput> {
+        let output = std::mem::take(&mut *self.output.lock().unwrap());
+        if output.is_empty() {
+            None
+        } else {
+            Some(output)
+        }
+    }
 }
@@ -1,38 +1,21 @@
 use super::context::RecordContext;
 use super::pipeline::PipelineStep;
 
-use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::path::{Path, PathBuf};
 use tokio::fs;
 use anyhow::{Context, Result};
-use reqwest::Client;
+use crate::openai::chat::{chat_completion, ChatRequest};
 use crate::paths::record_dir_from_audio;
-
-#[derive(Debug, Serialize, Deserialize)]
-struct ChatMessage {
-    role: String,
-    content: String,
-}
-
-#[derive(Debug, Serialize)]
-struct ChatRequest {
-    model: String,
-    messages: Vec<ChatMessage>,
-    temperature: f32,
-}
-
-#[derive(Debug, Deserialize)]
-struct ChatResponse {
-    choices: Vec<ChatChoice>,
-}
-
-#[derive(Debug, Deserialize)]
-struct ChatChoice {
-    message: ChatMessage,
-}
+use crate::prompts::builtin::ENTITY_EXTRACTION;
+use crate::prompts::registry::resolve_prompt;
+use crate::prompts::template::PromptTemplate;
+use crate::store::schema::{self, ENTITIES};
 
 pub struct EntityExtractionStep {
     pub openai_api_key: String,
+    /// Template to use instead of the active one (A/B runs).
+    pub prompt: Option<PromptTemplate>,
 }
 fn strip_json_fences(s: &str) -> &str {
     let s = s.trim();
@@ -93,6 +76,22 @@ impl PipelineStep for EntityExtractionStep {
     async fn run(&self, ctx: &RecordContext) -> Result<(), String> {
         self.run_inner(ctx).await.map_err(|e| e.to_string())
     }
+
+    fn depends_on(&self) -> Vec<&'static str> {
+        vec!["transcription"]
+    }
+
+    fn inputs(&self, _ctx: &RecordContext, record_dir: &Path) -> Vec<PathBuf> {
+        vec![record_dir.join("text.txt")]
+    }
+
+    fn outputs(&self, record_dir: &Path) -> Vec<PathBuf> {
+        vec![record_dir.join("entities.json")]
+    }
+
+    fn critical(&self) -> bool {
+        true
+    }
 }
 
 impl EntityExtractionStep {
@@ -114,30 +113,12 @@ impl EntityExtractionStep {
             .await
             .context("failed to read text.txt")?;
 
-        let system_prompt =
-            "You extract named entities from text.\n\
-             The document is a source, not the truth.\n\
-             Do not infer or assume.\n\
-             Only extract what is explicitly present.\n\
-             Return JSON only.";
-
-        let user_prompt = format!(
-            "Extract entities from the following document:\n\n{}",
-            document
-        );
+        let template = resolve_prompt(ctx, ENTITY_EXTRACTION, &self.prompt).await?;
 
-        println!("▶ EntityExtractionStep {} {} export tp file {}", system_prompt, user_prompt, entities_path.display());
+        println!("▶ EntityExtractionStep prompt {} export to file {}", template.id(), entities_path.display());
+        ctx.record_prompt(&template.id());
 
-        let messages = vec![
-            ChatMessage {
-                role: "system".into(),
-                content: system_prompt.into(),
-            },
-            ChatMessage {
-                role: "user".into(),
-                content: user_prompt,
-            },
-        ];
+        let messages = template.render(&[("DOCUMENT_TEXT", &document)]);
 
         let request = ChatRequest {
             model: "gpt-4.1-mini".into(),
@@ -145,41 +126,13 @@ impl EntityExtractionStep {
             temperature: 0.0,
         };
 
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
-            .user_agent("via/0.1 entity-extraction")
-            .build()
-            .context("failed to build reqwest client")?;
-
         println!(
             "▶ EntityExtractionStep sending request: model={}, chars={}",
             request.model,
             document.len()
         );
-        let response = client
-            .post("https://api.openai.com/v1/chat/completions")
-            .bearer_auth(&self.openai_api_key)
-            .json(&request)
-            .send()
-            .await
-            .context("failed to send OpenAI request")?;
-
-        if !response.status().is_success() {
-            let body = response.text().await.unwrap_or_default();
-            println!("▶ EntityExtractionStep OpenAI request failed {}", body);
-            anyhow::bail!("OpenAI error: {}", body);
-        }
-
-        let response: ChatResponse = response
-            .json()
-            .await
-            .context("invalid OpenAI response")?;
-
-        let raw_content = response
-            .choices
-            .get(0)
-            .map(|c| c.message.content.clone())
-            .unwrap_or_else(|| "[]".into());
+        let raw_content =
+            chat_completion(ctx, self.name(), &self.openai_api_key, &request).await?;
 
         println!("▶ raw OpenAI content:\n{}", raw_content);
 
@@ -202,13 +155,16 @@ impl EntityExtractionStep {
             }
         }
 
-        let pretty = serde_json::to_string_pretty(&entities)?;
+        let count = entities
+            .get("entities")
+            .and_then(|v| v.as_array())
+            .map(|a| a.len())
+            .unwrap_or(0);
+        ctx.record_count("entities", count);
 
-        fs::write(&entities_path, pretty)
-            .await
-            .context("failed to write entities.json")?;
+        ctx.ensure_not_cancelled()?;
 
-        let pretty = serde_json::to_string_pretty(&entities)?;
+        let pretty = schema::to_json(&ENTITIES, &entities)?;
 
         fs::write(&entities_path, pretty)
             .await
@@ -0,0 +1,58 @@
+use serde::Serialize;
+use std::fmt;
+
+use super::report::PipelineReport;
+
+/// Why `Pipeline::run` stopped. Failures of non-critical steps are not errors, see `PipelineReport`.
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PipelineError {
+    /// A critical step failed, steps after it did not run.
+    CriticalStepFailed {
+        step: String,
+        error: String,
+        report: PipelineReport,
+    },
+    Cancelled { report: PipelineReport },
+    DependencyCycle { steps: Vec<String> },
+    /// processing.json or the record dir could not be read or written.
+    Storage(String),
+}
+
+impl PipelineError {
+    pub fn report(&self) -> Option<&PipelineReport> {
+        match self {
+            Self::CriticalStepFailed { report, .. } | Self::Cancelled { report } => Some(report),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for PipelineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CriticalStepFailed { step, error, .. } => {
+                write!(f, "critical step {} failed: {}", step, error)
+            }
+            Self::Cancelled { .. } => write!(f, "processing cancelled"),
+            Self::DependencyCycle { steps } => {
+                write!(f, "dependency cycle between {}", steps.join(", "))
+            }
+            Self::Storage(err) => write!(f, "pipeline storage error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for PipelineError {}
+
+impl From<anyhow::Error> for PipelineError {
+    fn from(err: anyhow::Error) -> Self {
+        Self::Storage(err.to_string())
+    }
+}
+
+impl From<PipelineError> for String {
+    fn from(err: PipelineError) -> Self {
+        err.to_string()
+    }
+}
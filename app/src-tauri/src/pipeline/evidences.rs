@@ -1,11 +1,15 @@
 use anyhow::{Context, Result};
-use tokio::fs;
+use std::path::Path;
 use walkdir::WalkDir;
 
+use crate::paths::record_dir_from_audio;
 use crate::pipeline::context::RecordContext;
 use crate::pipeline::pipeline::PipelineStep;
-use crate::store::evidence::{EvidenceRecord, EvidenceStore};
+use crate::processing::processing::ProcessingFile;
+use crate::store::evidence::EvidenceStore;
 
+/// Keeps the global evidence.json current. A run replaces the processed document's share,
+/// `EvidencesPipeline::rebuild` merges every document's evidence.json again.
 pub struct EvidencesPipeline;
 
 #[async_trait::async_trait]
@@ -21,21 +25,43 @@ impl PipelineStep for EvidencesPipeline {
 
 impl EvidencesPipeline {
     async fn run_inner(&self, ctx: &RecordContext) -> Result<()> {
-        let data_root = ctx
-            .base_dir
-            .parent()
-            .and_then(|p| p.parent())
-            .context("base_dir is not data/YYYY/MM")?
-            .to_path_buf();
+        let data_root = ctx.data_root()?;
+        let record_dir = record_dir_from_audio(&ctx.base_dir, &ctx.audio_file).await?;
+        let doc_id = ProcessingFile::load(&record_dir).await?.doc_id;
+
+        // globale evidence.json, concurrent jobs must not lose each other's delta
+        let mut root_store = EvidenceStore::lock_and_load(&data_root).await?;
+        if root_store.has_untracked_evidence() {
+            println!("▶ EvidencesPipeline: evidence without per-document contributions, rebuilding");
+            drop(root_store);
+            return Self::rebuild(&data_root).await;
+        }
+
+        root_store.remove_document(&doc_id);
+
+        if record_dir.join("evidence.json").exists() {
+            let store = EvidenceStore::load_or_create(&record_dir).await?;
+            for record in store.all() {
+                root_store.merge_document(&doc_id, record);
+            }
+        }
+
+        root_store.save().await?;
+        println!("▶ EvidencesPipeline applied delta of {}", doc_id);
 
+        Ok(())
+    }
+
+    /// Full rebuild from every evidence.json below the data root. User confirmations are kept.
+    pub async fn rebuild(data_root: &Path) -> Result<()> {
         println!("▶ EvidencesPipeline scanning {}", data_root.display());
 
-        // globale evidence.json
-        let mut root_store = EvidenceStore::load_or_create(&data_root).await?;
+        let mut root_store = EvidenceStore::lock_and_load(data_root).await?;
+        root_store.retain_confirmations();
 
         let mut evidence_files_found = 0;
 
-        for entry in WalkDir::new(&data_root)
+        for entry in WalkDir::new(data_root)
             .into_iter()
             .filter_map(Result::ok)
         {
@@ -44,18 +70,31 @@ impl EvidencesPipeline {
             }
 
             // skip globale evidence.json, sonst Selbstaggregation
-            if entry.path().parent() == Some(data_root.as_path()) {
+            if entry.path().parent() == Some(data_root) {
                 continue;
             }
 
-            evidence_files_found += 1;
-
             let record_dir = entry
                 .path()
                 .parent()
                 .context("evidence.json without parent dir")?;
 
-            let store = EvidenceStore::load_or_create(record_dir).await?;
+            let doc_id = match ProcessingFile::load(record_dir).await {
+                Ok(p) => p.doc_id,
+                Err(err) => {
+                    println!("⚠ no processing.json next to {} → {}", entry.path().display(), err);
+                    continue;
+                }
+            };
+
+            let store = match EvidenceStore::load_or_create(record_dir).await {
+                Ok(store) => store,
+                Err(err) => {
+                    println!("⚠ skipping {} → {}", entry.path().display(), err);
+                    continue;
+                }
+            };
+            evidence_files_found += 1;
 
             println!(
                 "▶ found evidence.json at {} with {} records",
@@ -64,9 +103,7 @@ impl EvidencesPipeline {
             );
 
             for record in store.all() {
-                // jede EvidenceRecord enthält ihre Dokumente bereits
-                // wir fügen sie 1:1 zur globalen Evidence hinzu
-                root_store.merge_record(record);
+                root_store.merge_document(&doc_id, record);
             }
         }
 
@@ -83,4 +120,4 @@ impl EvidencesPipeline {
 
         Ok(())
     }
-}
\ No newline at end of file
+}
@@ -1,4 +1,6 @@
 use anyhow::{Context, Result};
+use chrono::Utc;
+use std::collections::HashSet;
 
 use crate::pipeline::context::RecordContext;
 use crate::pipeline::pipeline::PipelineStep;
@@ -13,6 +15,10 @@ impl PipelineStep for KnowledgeBuilder {
         "knowledge-builder"
     }
 
+    fn depends_on(&self) -> Vec<&'static str> {
+        vec!["evidences"]
+    }
+
     async fn run(&self, ctx: &RecordContext) -> Result<(), String> {
         self.run_inner(ctx).await.map_err(|e| e.to_string())
     }
@@ -28,16 +34,36 @@ impl KnowledgeBuilder {
             .context("base_dir is not data/YYYY/MM")?
             .to_path_buf();
 
+        Self::build(&data_root).await
+    }
+
+    /// Derives knowledge.json from the global evidence.
+    pub async fn build(data_root: &std::path::Path) -> Result<()> {
         println!("▶ KnowledgeBuilder scanning {}", data_root.display());
 
-        let evidence_store = EvidenceStore::load_or_create(&data_root).await?;
-        let mut knowledge = KnowledgeStore::load_or_create(&data_root).await?;
+        let evidence_store = EvidenceStore::load_or_create(data_root).await?;
+        let mut knowledge = KnowledgeStore::lock_and_load(data_root).await?;
 
         let mut created = 0;
         let mut updated = 0;
+        let mut supported = HashSet::new();
 
         for ev in evidence_store.all() {
             let id = Self::knowledge_id(ev);
+            supported.insert(id.clone());
+
+            // evidence is replaced per document, so confidence and documents are taken as they are now
+            if let Some(existing) = knowledge.get_mut(&id) {
+                let confidence = ev.avg_confidence();
+                // untouched records stay out of the change history
+                if existing.confidence != confidence || existing.source_documents != ev.documents {
+                    existing.confidence = confidence;
+                    existing.source_documents = ev.documents.clone();
+                    existing.updated_at = Some(Utc::now().to_rfc3339());
+                    updated += 1;
+                }
+                continue;
+            }
 
             let record = KnowledgeRecord::new(
                 id.clone(),
@@ -50,29 +76,29 @@ impl KnowledgeBuilder {
                 ev.documents.clone(),
             );
 
-            if knowledge.get_mut(&id).is_some() {
-                updated += 1;
-            } else {
-                created += 1;
-            }
-
+            created += 1;
             knowledge.append_or_update(record);
         }
 
+        let retracted = knowledge.retract_unsupported("KNOWLEDGE_BUILDER", &supported);
+
+        knowledge.changed_by("KNOWLEDGE_BUILDER", "evidence changed");
+
         knowledge.save().await?;
 
         println!(
-            "▶ KnowledgeBuilder finished (created={}, updated={})",
-            created, updated
+            "▶ KnowledgeBuilder finished (created={}, updated={}, retracted={})",
+            created, updated, retracted
         );
 
         Ok(())
     }
 
-    fn knowledge_id(ev: &crate::store::evidence::EvidenceRecord) -> String {
+    /// Knowledge record id derived from an evidence record.
+    pub(crate) fn knowledge_id(ev: &crate::store::evidence::EvidenceRecord) -> String {
         format!(
             "{}:{}|{}:{}",
-            ev.subject_type.to_lowercase(),
+            ev.subject_type.as_str(),
             ev.subject_value.to_lowercase(),
             ev.predicate,
             ev.object_value.to_lowercase()
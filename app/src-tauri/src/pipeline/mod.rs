@@ -1,5 +1,7 @@
 pub mod context;
 pub mod pipeline;
+pub mod report;
+pub mod error;
 pub mod transcription;
 pub mod entities;
 pub mod signals;
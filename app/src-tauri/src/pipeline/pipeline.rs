@@ -1,111 +1,399 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Instant, SystemTime};
 use super::context::RecordContext;
+use super::error::PipelineError;
+use super::report::PipelineReport;
 use async_trait::async_trait;
 use chrono::Utc;
-use serde::Deserialize;
+use tokio::task::JoinSet;
 use crate::paths::record_dir_from_audio;
 use crate::agents::agent::Agent;
-use crate::processing::model::ProcessingStep;
+use crate::processing::model::{ProcessingStep, StepOutput};
 use crate::processing::processing::ProcessingFile;
+use crate::processing::utils::sha256_file;
 
 #[async_trait]
 pub trait PipelineStep: Send + Sync {
     fn name(&self) -> &'static str;
     async fn run(&self, ctx: &RecordContext) -> Result<(), String>;
+
+    /// Steps (by name) that have to finish first. Steps missing from the pipeline count as done.
+    fn depends_on(&self) -> Vec<&'static str> {
+        Vec::new()
+    }
+
+    /// Files the step reads. A newer input makes the outputs stale.
+    fn inputs(&self, _ctx: &RecordContext, _record_dir: &Path) -> Vec<PathBuf> {
+        Vec::new()
+    }
+
+    /// Files the step writes. Steps without declared outputs always run.
+    fn outputs(&self, _record_dir: &Path) -> Vec<PathBuf> {
+        Vec::new()
+    }
+
+    /// A failing critical step stops the pipeline, later steps would only work on missing input.
+    fn critical(&self) -> bool {
+        false
+    }
 }
 
+/// Lets agents take part in the step graph.
+struct AgentStep(Box<dyn Agent>);
+
+#[async_trait]
+impl PipelineStep for AgentStep {
+    fn name(&self) -> &'static str {
+        self.0.name()
+    }
+
+    async fn run(&self, ctx: &RecordContext) -> Result<(), String> {
+        self.0.run_document(ctx).await.map_err(|e| e.to_string())
+    }
+
+    fn depends_on(&self) -> Vec<&'static str> {
+        self.0.depends_on()
+    }
+
+    fn inputs(&self, ctx: &RecordContext, record_dir: &Path) -> Vec<PathBuf> {
+        self.0.inputs(ctx, record_dir)
+    }
+
+    fn outputs(&self, record_dir: &Path) -> Vec<PathBuf> {
+        self.0.outputs(record_dir)
+    }
 
+    fn critical(&self) -> bool {
+        self.0.critical()
+    }
+}
+
+/// Steps and agents of one document, run as a dependency graph.
+/// Steps whose dependencies are satisfied run concurrently, each with its own output recorder.
 pub struct Pipeline {
-    steps: Vec<Box<dyn PipelineStep>>,
-    agents: Vec<Box<dyn Agent>>,
+    steps: Vec<Arc<dyn PipelineStep>>,
+    /// Steps that run even if their outputs are up to date.
+    force: HashSet<String>,
 }
 
 impl Pipeline {
     pub fn new() -> Self {
         Self {
             steps: Vec::new(),
-            agents: Vec::new(),
+            force: HashSet::new(),
         }
     }
 
+    pub fn force(mut self, steps: &[String]) -> Self {
+        self.force.extend(steps.iter().cloned());
+        self
+    }
+
+    pub fn step_names(&self) -> Vec<&'static str> {
+        self.steps.iter().map(|s| s.name()).collect()
+    }
+
     pub fn add_step(mut self, step: impl PipelineStep + 'static) -> Self {
-        self.steps.push(Box::new(step));
+        self.steps.push(Arc::new(step));
         self
     }
 
     pub fn add_agent(mut self, agent: impl Agent + 'static) -> Self {
-        self.agents.push(Box::new(agent));
+        self.steps.push(Arc::new(AgentStep(Box::new(agent))));
         self
     }
 
-    pub async fn run(&self, ctx: &RecordContext) -> Result<(), String> {
-        let record_dir = record_dir_from_audio(&ctx.base_dir, &ctx.audio_file)
-            .await
-            .map_err(|e| e.to_string())?;
+    /// Runs the graph. Failures of non-critical steps end up in the report and block only their
+    /// dependents; a failing critical step stops the run once the steps running beside it are done.
+    pub async fn run(&self, ctx: &RecordContext) -> Result<PipelineReport, PipelineError> {
+        let record_dir = record_dir_from_audio(&ctx.base_dir, &ctx.audio_file).await?;
 
         let mut processing =
-            ProcessingFile::load_or_create(&record_dir, &ctx.audio_file)
-                .await
-                .map_err(|e| e.to_string())?;
-
-        // ---------- deterministic pipeline steps ----------
-        for step in &self.steps {
-            let step_name = step.name().to_string();
-            println!("▶ Pipeline step: {}", step_name);
-
-            let result = step.run(ctx).await;
-
-            let step_entry = ProcessingStep {
-                status: if result.is_ok() { "done" } else { "error" }.into(),
-                output: None,
-                finished_at: Some(Utc::now().to_rfc3339()),
-            };
-
-            if let Err(err) = result {
-                processing
-                    .errors
-                    .push(format!("{}: {}", step_name, err));
+            ProcessingFile::load_or_create(&record_dir, &ctx.audio_file).await?;
+
+        let in_pipeline: HashSet<&'static str> = self.steps.iter().map(|s| s.name()).collect();
+        let mut finished: HashSet<&'static str> = HashSet::new();
+        // steps that actually ran, their dependents have to run again
+        let mut executed: HashSet<&'static str> = HashSet::new();
+        // steps without a usable result, their dependents are blocked
+        let mut failed: HashSet<&'static str> = HashSet::new();
+        let mut critical_failure: Option<(String, String)> = None;
+        let mut report = PipelineReport::default();
+        let mut pending: Vec<Arc<dyn PipelineStep>> = self.steps.clone();
+        let total = pending.len().max(1);
+
+        while !pending.is_empty() {
+            if ctx.is_cancelled() || critical_failure.is_some() {
+                // steps not started yet keep their previous entry
+                println!("▶ Pipeline stopped, {} steps not started", pending.len());
+                for step in &pending {
+                    report.push(step.name(), "not_run", step.critical(), None);
+                }
+                break;
             }
 
-            processing.steps.insert(step_name, step_entry);
-            processing
-                .save(&record_dir)
-                .await
-                .map_err(|e| e.to_string())?;
-        }
+            let (ready, waiting): (Vec<_>, Vec<_>) = pending.into_iter().partition(|step| {
+                step.depends_on()
+                    .iter()
+                    .all(|dep| !in_pipeline.contains(dep) || finished.contains(dep))
+            });
+
+            if ready.is_empty() {
+                return Err(PipelineError::DependencyCycle {
+                    steps: waiting.iter().map(|s| s.name().to_string()).collect(),
+                });
+            }
+            pending = waiting;
+
+            let mut wave = JoinSet::new();
+            for step in ready {
+                let step_name = step.name();
+                let critical = step.critical();
+
+                if let Some(dep) = step.depends_on().into_iter().find(|dep| failed.contains(dep)) {
+                    println!("▶ Pipeline step: {} (blocked, {} failed)", step_name, dep);
+                    let error = format!("dependency {} failed", dep);
+                    if critical && critical_failure.is_none() {
+                        critical_failure = Some((step_name.to_string(), error.clone()));
+                    }
+                    report.push(step_name, "blocked", critical, Some(error));
+                    failed.insert(step_name);
+                    finished.insert(step_name);
+                    ctx.emit_step(step_name, "blocked", progress(finished.len(), total));
+                    continue;
+                }
+
+                let mut dependency_ran = step.depends_on().iter().any(|dep| executed.contains(dep));
+                let mut forced = self.force.contains(step_name);
+                // a retention policy removed what the step works with, running it again would fail
+                if (forced || dependency_ran) && purged_files(step.as_ref(), ctx, &record_dir, &processing) {
+                    println!("▶ Pipeline step: {} (files purged, not run again)", step_name);
+                    forced = false;
+                    dependency_ran = false;
+                }
+
+                if !forced
+                    && !dependency_ran
+                    && is_up_to_date(step.as_ref(), ctx, &record_dir, &processing).await
+                {
+                    println!("▶ Pipeline step: {} (up to date, skipped)", step_name);
+                    report.push(step_name, "skipped", critical, None);
+                    finished.insert(step_name);
+                    ctx.emit_step(step_name, "skipped", progress(finished.len(), total));
+                    continue;
+                }
 
-        // ---------- heuristic / AI agents ----------
-        for agent in &self.agents {
-            let agent_name = agent.name().to_string();
-            println!("▶ Agent: {}", agent_name);
+                println!("▶ Pipeline step: {}", step_name);
+                ctx.emit_step(step_name, "started", progress(finished.len(), total));
 
-            let result = agent.run_document(ctx).await;
+                // own recorder, concurrent steps must not mix their usage and attempts
+                let step_ctx = RecordContext {
+                    output: Default::default(),
+                    ..ctx.clone()
+                };
+                let inputs = step.inputs(ctx, &record_dir);
+                let outputs = step.outputs(&record_dir);
+                wave.spawn(async move {
+                    let started_at = Utc::now().to_rfc3339();
+                    let clock = Instant::now();
+                    let input_hashes = hash_inputs(&step_ctx.base_dir, &inputs).await;
 
-            let step_entry = ProcessingStep {
-                status: if result.is_ok() { "done" } else { "error" }.into(),
-                output: None,
-                finished_at: Some(Utc::now().to_rfc3339()),
-            };
+                    let result = step.run(&step_ctx).await;
+                    let cancelled = result.is_err() && step_ctx.is_cancelled();
 
-            if let Err(err) = result {
-                processing
-                    .errors
-                    .push(format!("{}: {}", agent_name, err));
+                    let mut output = step_ctx.take_output().unwrap_or_default();
+                    output.input_hashes = input_hashes;
+                    if result.is_ok() {
+                        output.artifacts = outputs
+                            .iter()
+                            .filter(|p| p.exists())
+                            .map(|p| relative_key(&step_ctx.base_dir, p))
+                            .collect();
+                    }
+
+                    StepRun {
+                        name: step.name(),
+                        critical,
+                        result,
+                        cancelled,
+                        output,
+                        started_at,
+                        duration_ms: clock.elapsed().as_millis() as u64,
+                    }
+                });
             }
 
-            processing.steps.insert(agent_name, step_entry);
-            processing
-                .save(&record_dir)
-                .await
-                .map_err(|e| e.to_string())?;
+            while let Some(joined) = wave.join_next().await {
+                let StepRun {
+                    name: step_name,
+                    critical,
+                    result,
+                    cancelled,
+                    output,
+                    started_at,
+                    duration_ms,
+                } = joined.map_err(|e| PipelineError::Storage(e.to_string()))?;
+
+                let status = match (&result, cancelled) {
+                    (Ok(()), _) => "done",
+                    (Err(_), true) => "cancelled",
+                    (Err(_), false) => "error",
+                };
+                let step_entry = ProcessingStep {
+                    status: status.into(),
+                    output: (!output.is_empty()).then_some(output),
+                    started_at: Some(started_at),
+                    finished_at: Some(Utc::now().to_rfc3339()),
+                    duration_ms: Some(duration_ms),
+                };
+
+                let error = result.err();
+                if let (Some(err), false) = (&error, cancelled) {
+                    processing
+                        .errors
+                        .push(format!("{}: {}", step_name, err));
+                    if critical && critical_failure.is_none() {
+                        critical_failure = Some((step_name.to_string(), err.clone()));
+                    }
+                }
+                if status == "done" {
+                    // errors of earlier runs of this step are resolved now
+                    processing.clear_step_errors(step_name);
+                } else {
+                    failed.insert(step_name);
+                }
+                report.push(step_name, status, critical, error);
+
+                processing.steps.insert(step_name.to_string(), step_entry);
+                processing.save(&record_dir).await?;
+
+                finished.insert(step_name);
+                executed.insert(step_name);
+                ctx.emit_step(step_name, status, progress(finished.len(), total));
+            }
+        }
+
+        if ctx.is_cancelled() {
+            return Err(PipelineError::Cancelled { report });
+        }
+        if let Some((step, error)) = critical_failure {
+            return Err(PipelineError::CriticalStepFailed { step, error, report });
         }
 
         processing.finished_at = Some(Utc::now().to_rfc3339());
-        processing
-            .save(&record_dir)
-            .await
-            .map_err(|e| e.to_string())?;
+        processing.save(&record_dir).await?;
+
+        Ok(report)
+    }
+}
+
+/// What a spawned step hands back to the scheduler.
+struct StepRun {
+    name: &'static str,
+    critical: bool,
+    result: Result<(), String>,
+    cancelled: bool,
+    output: StepOutput,
+    started_at: String,
+    duration_ms: u64,
+}
+
+/// 5..95, start and end are emitted by the callers.
+fn progress(done: usize, total: usize) -> u8 {
+    (5 + 90 * done / total) as u8
+}
+
+/// Done before, all outputs present and the inputs unchanged since.
+/// Inputs are compared by hash; entries written before hashes were recorded fall back to mtimes.
+/// Files removed by a retention policy count as present and unchanged.
+async fn is_up_to_date(
+    step: &dyn PipelineStep,
+    ctx: &RecordContext,
+    record_dir: &Path,
+    processing: &ProcessingFile,
+) -> bool {
+    let Some(entry) = processing.steps.get(step.name()) else {
+        return false;
+    };
+    if entry.status != "done" {
+        return false;
+    }
 
-        Ok(())
+    let outputs = step.outputs(record_dir);
+    if outputs.is_empty() || !outputs.iter().all(|p| p.exists() || processing.is_purged(p)) {
+        return false;
     }
+
+    let inputs = step.inputs(ctx, record_dir);
+    let recorded = entry
+        .output
+        .as_ref()
+        .map(|o| &o.input_hashes)
+        .filter(|h| !h.is_empty());
+
+    match recorded {
+        Some(recorded) => {
+            let recorded: BTreeMap<String, String> = recorded
+                .iter()
+                .filter(|(key, _)| {
+                    let path = ctx.base_dir.join(key);
+                    path.exists() || !processing.is_purged(&path)
+                })
+                .map(|(key, hash)| (key.clone(), hash.clone()))
+                .collect();
+            hash_inputs(&ctx.base_dir, &inputs).await == recorded
+        }
+        None => {
+            let outputs: Vec<PathBuf> = outputs.into_iter().filter(|p| p.exists()).collect();
+            outputs.is_empty() || inputs_older_than(&inputs, &outputs)
+        }
+    }
+}
+
+/// An input or output of the step was removed by a retention policy.
+fn purged_files(step: &dyn PipelineStep, ctx: &RecordContext, record_dir: &Path, processing: &ProcessingFile) -> bool {
+    step.inputs(ctx, record_dir)
+        .into_iter()
+        .chain(step.outputs(record_dir))
+        .any(|p| !p.exists() && processing.is_purged(&p))
+}
+
+fn inputs_older_than(inputs: &[PathBuf], outputs: &[PathBuf]) -> bool {
+    let Some(oldest_output) = outputs
+        .iter()
+        .map(|p| modified(p))
+        .collect::<Option<Vec<_>>>()
+        .and_then(|times| times.into_iter().min())
+    else {
+        return false;
+    };
+
+    inputs
+        .iter()
+        .filter_map(|p| modified(p))
+        .all(|input| input <= oldest_output)
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// sha256 per existing input, keyed relative to the month dir.
+async fn hash_inputs(base_dir: &Path, inputs: &[PathBuf]) -> BTreeMap<String, String> {
+    let mut hashes = BTreeMap::new();
+    for input in inputs {
+        if let Ok(hash) = sha256_file(input).await {
+            hashes.insert(relative_key(base_dir, input), hash);
+        }
+    }
+    hashes
+}
+
+fn relative_key(base_dir: &Path, path: &Path) -> String {
+    path.strip_prefix(base_dir)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
 }
@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+
+/// What happened to one step in a pipeline run.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StepReport {
+    pub name: String,
+    /// done | skipped (up to date) | error | cancelled | blocked (a dependency failed) | not_run
+    pub status: String,
+    pub critical: bool,
+    pub error: Option<String>,
+}
+
+/// Outcome of `Pipeline::run`, handed to the caller and the UI.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct PipelineReport {
+    pub steps: Vec<StepReport>,
+}
+
+impl PipelineReport {
+    pub(crate) fn push(&mut self, name: &str, status: &str, critical: bool, error: Option<String>) {
+        self.steps.push(StepReport {
+            name: name.into(),
+            status: status.into(),
+            critical,
+            error,
+        });
+    }
+
+    /// Some non-critical steps failed or could not run, the rest went through.
+    pub fn is_partial(&self) -> bool {
+        self.steps
+            .iter()
+            .any(|s| s.status == "error" || s.status == "blocked")
+    }
+
+    pub fn failed_steps(&self) -> Vec<String> {
+        self.steps_with_status("error")
+    }
+
+    /// Did not run because a step they depend on failed.
+    pub fn blocked_steps(&self) -> Vec<String> {
+        self.steps_with_status("blocked")
+    }
+
+    /// "fehlgeschlagen: a, b; blockiert: c" for the progress message of a partial run.
+    pub fn partial_summary(&self) -> String {
+        let mut parts = Vec::new();
+        let failed = self.failed_steps();
+        if !failed.is_empty() {
+            parts.push(format!("fehlgeschlagen: {}", failed.join(", ")));
+        }
+        let blocked = self.blocked_steps();
+        if !blocked.is_empty() {
+            parts.push(format!("blockiert: {}", blocked.join(", ")));
+        }
+        parts.join("; ")
+    }
+
+    fn steps_with_status(&self, status: &str) -> Vec<String> {
+        self.steps
+            .iter()
+            .filter(|s| s.status == status)
+            .map(|s| s.name.clone())
+            .collect()
+    }
+
+    pub fn extend(&mut self, other: PipelineReport) {
+        self.steps.extend(other.steps);
+    }
+}
@@ -1,12 +1,17 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
 use anyhow::{Context, Result};
 use tokio::fs;
 use walkdir::WalkDir;
 
+use crate::paths::record_dir_from_audio;
 use crate::pipeline::context::RecordContext;
 use crate::pipeline::pipeline::PipelineStep;
 use crate::processing::processing::ProcessingFile;
+use crate::store::atomic::write_atomic;
+use crate::store::lock::lock_file;
+use crate::store::schema::{self, SIGNALS};
 
 #[derive(Debug, Deserialize)]
 struct EntitiesFile {
@@ -19,7 +24,8 @@ struct Entity {
     entity_type: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(default)]
 struct Signal {
     key: String,
     #[serde(rename = "type")]
@@ -27,6 +33,9 @@ struct Signal {
     value: String,
     count: usize,
     documents: Vec<String>,
+    /// doc_id -> mentions, so a document's share can be taken out again
+    #[serde(default)]
+    per_document: BTreeMap<String, usize>,
 }
 
 fn normalize(s: &str) -> String {
@@ -36,6 +45,8 @@ fn normalize(s: &str) -> String {
         .replace(',', "")
 }
 
+/// Keeps data/signals.json current. A run only applies the delta of the processed document,
+/// `SignalsPipeline::rebuild` rescans every entities.json.
 pub struct SignalsPipeline;
 
 #[async_trait::async_trait]
@@ -51,104 +62,170 @@ impl PipelineStep for SignalsPipeline {
 
 impl SignalsPipeline {
     async fn run_inner(&self, ctx: &RecordContext) -> Result<()> {
-        let data_root = ctx
-            .base_dir
-            .parent()
-            .and_then(|p| p.parent())
-            .context("base_dir is not data/YYYY/MM")?
-            .to_path_buf();
+        let data_root = ctx.data_root()?;
+        let record_dir = record_dir_from_audio(&ctx.base_dir, &ctx.audio_file).await?;
+        let doc_id = ProcessingFile::load(&record_dir).await?.doc_id;
+
+        // concurrent jobs must not lose each other's delta
+        let guard = lock_file(&data_root.join("signals.json")).await;
+        let Some(mut signals) = load_signals(&data_root).await? else {
+            println!("▶ SignalsPipeline: signals.json without per-document counts, rebuilding");
+            drop(guard);
+            return Self::rebuild(&data_root).await;
+        };
+
+        remove_document(&mut signals, &doc_id);
+        let entities_path = record_dir.join("entities.json");
+        if entities_path.exists() {
+            add_document(&mut signals, &doc_id, &entities_path).await?;
+        }
+
+        println!("▶ SignalsPipeline applied delta of {}", doc_id);
+        save_signals(&data_root, signals).await
+    }
+
+    /// Full rebuild from every entities.json below the data root.
+    pub async fn rebuild(data_root: &Path) -> Result<()> {
         println!("▶ SignalsPipeline scanning data root: {}", data_root.display());
+        let _guard = lock_file(&data_root.join("signals.json")).await;
         let mut signals: HashMap<String, Signal> = HashMap::new();
         let mut entities_files_found = 0;
-        for entry in WalkDir::new(&data_root)
+        for entry in WalkDir::new(data_root)
             .into_iter()
             .filter_map(Result::ok)
         {
             if entry.file_name() != "entities.json" {
                 continue;
             }
-            let processing =
-                ProcessingFile::load(entry.path().parent().unwrap())
-                    .await
-                    .map_err(|e| e.to_string());
-            
-            entities_files_found += 1;
-
 
             let record_dir = entry
                 .path()
                 .parent()
                 .context("entities.json without parent dir")?;
 
-            let doc_id = record_dir
-                .file_name()
-                .context("record dir has no name")?
-                .to_string_lossy()
-                .to_string();
-            let doc_id = processing.unwrap().doc_id;
-            
-            println!("▶ found entities.json at {} with doc_uid: {}", entry.path().display(),doc_id);
-
-            let content = fs::read_to_string(entry.path())
-                .await
-                .with_context(|| format!("failed to read {}", entry.path().display()))?;
-
-
-            let wrapper: EntitiesFile = match serde_json::from_str(&content) {
-                Ok(w) => w,
+            let doc_id = match ProcessingFile::load(record_dir).await {
+                Ok(p) => p.doc_id,
                 Err(err) => {
-                    println!(
-                        "⚠ invalid entities.json at {} → {}",
-                        entry.path().display(),
-                        err
-                    );
+                    println!("⚠ no processing.json next to {} → {}", entry.path().display(), err);
                     continue;
                 }
             };
 
-            println!("▶ parsed {} entities", wrapper.entities.len());
+            entities_files_found += 1;
+            println!("▶ found entities.json at {} with doc_uid: {}", entry.path().display(), doc_id);
 
-            for entity in wrapper.entities {
-                let normalized = normalize(&entity.text);
-                let key = format!(
-                    "{}:{}",
-                    entity.entity_type.to_lowercase(),
-                    normalized
-                );
+            if let Err(err) = add_document(&mut signals, &doc_id, entry.path()).await {
+                println!("⚠ skipping {} → {}", entry.path().display(), err);
+            }
+        }
+        println!("▶ total entities.json files found: {}", entities_files_found);
 
-                let signal = signals.entry(key.clone()).or_insert(Signal {
-                    key,
-                    signal_type: entity.entity_type.to_lowercase(),
-                    value: entity.text.clone(),
-                    count: 0,
-                    documents: Vec::new(),
-                });
+        save_signals(data_root, signals).await
+    }
 
-                signal.count += 1;
+    /// Takes a deleted document's mentions out of signals.json.
+    pub async fn remove_document(data_root: &Path, doc_id: &str) -> Result<()> {
+        let guard = lock_file(&data_root.join("signals.json")).await;
+        let Some(mut signals) = load_signals(data_root).await? else {
+            drop(guard);
+            return Self::rebuild(data_root).await;
+        };
 
-                if !signal.documents.contains(&doc_id) {
-                    signal.documents.push(doc_id.clone());
-                }
-            }
-            //let entities: Vec<Entity> = serde_json::from_str(&content)
-            //  .with_context(|| format!("invalid JSON in {}", entry.path().display()))?;
+        remove_document(&mut signals, doc_id);
+        save_signals(data_root, signals).await
+    }
+}
 
+/// None if the file predates per-document counts, a delta cannot be applied then.
+async fn load_signals(data_root: &Path) -> Result<Option<HashMap<String, Signal>>> {
+    let path = data_root.join("signals.json");
+    if !path.exists() {
+        return Ok(Some(HashMap::new()));
+    }
+
+    let mut value = schema::load(&path, &SIGNALS).await?.value;
+    let list: Vec<Signal> =
+        serde_json::from_value(value["records"].take()).context("invalid signals.json")?;
+
+    if list.iter().any(|s| s.count > 0 && s.per_document.is_empty()) {
+        return Ok(None);
+    }
+
+    Ok(Some(list.into_iter().map(|s| (s.key.clone(), s)).collect()))
+}
+
+async fn save_signals(data_root: &Path, signals: HashMap<String, Signal>) -> Result<()> {
+    let mut all_signals: Vec<Signal> = signals.into_values().collect();
+    all_signals.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.key.cmp(&b.key)));
+
+    let output_path = data_root.join("signals.json");
+    let json = schema::to_json(&SIGNALS, &serde_json::json!({ "records": all_signals }))?;
+    write_atomic(&output_path, json.as_bytes())
+        .await
+        .context("failed to write signals.json")?;
+
+    println!("▶ SignalsPipeline wrote {}", output_path.display());
+
+    Ok(())
+}
+
+fn remove_document(signals: &mut HashMap<String, Signal>, doc_id: &str) {
+    signals.retain(|_, signal| {
+        if let Some(count) = signal.per_document.remove(doc_id) {
+            signal.count = signal.count.saturating_sub(count);
+            signal.documents.retain(|d| d != doc_id);
+        }
+        signal.count > 0
+    });
+}
+
+async fn add_document(
+    signals: &mut HashMap<String, Signal>,
+    doc_id: &str,
+    entities_path: &Path,
+) -> Result<()> {
+    let content = fs::read_to_string(entities_path)
+        .await
+        .with_context(|| format!("failed to read {}", entities_path.display()))?;
+
+    let wrapper: EntitiesFile = match serde_json::from_str(&content) {
+        Ok(w) => w,
+        Err(err) => {
+            println!(
+                "⚠ invalid entities.json at {} → {}",
+                entities_path.display(),
+                err
+            );
+            return Ok(());
+        }
+    };
+
+    println!("▶ parsed {} entities", wrapper.entities.len());
+
+    for entity in wrapper.entities {
+        let normalized = normalize(&entity.text);
+        let key = format!(
+            "{}:{}",
+            entity.entity_type.to_lowercase(),
+            normalized
+        );
+
+        let signal = signals.entry(key.clone()).or_insert(Signal {
+            key,
+            signal_type: entity.entity_type.to_lowercase(),
+            value: entity.text.clone(),
+            count: 0,
+            documents: Vec::new(),
+            per_document: BTreeMap::new(),
+        });
+
+        signal.count += 1;
+        *signal.per_document.entry(doc_id.to_string()).or_insert(0) += 1;
+
+        if !signal.documents.iter().any(|d| d == doc_id) {
+            signal.documents.push(doc_id.to_string());
         }
-        println!("▶ total entities.json files found2: {}", entities_files_found);
-        let mut all_signals: Vec<Signal> = signals.into_values().collect();
-        all_signals.sort_by(|a, b| b.count.cmp(&a.count));
-
-        let output_path = data_root.join("signals.json");
-        println!("▶ wr {}", output_path.display());
-        fs::write(
-            &output_path,
-            serde_json::to_string_pretty(&all_signals)?,
-        )
-            .await
-            .context("failed to write signals.json")?;
-
-        println!("▶ SignalsPipeline wrote {}", output_path.display());
-
-        Ok(())
     }
+
+    Ok(())
 }
@@ -1,13 +1,9 @@
 use super::context::RecordContext;
 use super::pipeline::PipelineStep;
-use serde::Deserialize;
-use reqwest::{Client, multipart::{Form, Part}};
 use anyhow::Context;
+use std::path::{Path, PathBuf};
+use crate::openai::whisper::transcribe;
 use crate::paths::record_dir_from_audio;
-#[derive(Debug, Deserialize)]
-struct WhisperResponse {
-    text: String,
-}
 
 pub struct TranscriptionStep {
     pub openai_api_key: String,
@@ -22,6 +18,18 @@ impl PipelineStep for TranscriptionStep {
     async fn run(&self, ctx: &RecordContext) -> Result<(), String> {
         self.run_inner(ctx).await.map_err(|e| e.to_string())
     }
+
+    fn inputs(&self, ctx: &RecordContext, _record_dir: &Path) -> Vec<PathBuf> {
+        vec![ctx.audio_file.clone()]
+    }
+
+    fn outputs(&self, record_dir: &Path) -> Vec<PathBuf> {
+        vec![record_dir.join("text.txt")]
+    }
+
+    fn critical(&self) -> bool {
+        true
+    }
 }
 
 impl TranscriptionStep {
@@ -39,36 +47,10 @@ impl TranscriptionStep {
             .await
             .context("failed to read audio file")?;
 
-        let part = Part::bytes(file_content)
-            .file_name("audio.wav")
-            .mime_str("audio/wav")?;
-
-        let form = Form::new()
-            .part("file", part)
-            .text("language", "de")
-            .text("model", "whisper-1");
-
-        let client = Client::new();
-
-        let response = client
-            .post("https://api.openai.com/v1/audio/transcriptions")
-            .bearer_auth(&self.openai_api_key)
-            .multipart(form)
-            .send()
-            .await
-            .context("failed to send whisper request")?;
-
-        if !response.status().is_success() {
-            let body = response.text().await.unwrap_or_default();
-            anyhow::bail!("OpenAI error: {}", body);
-        }
-
-        let whisper: WhisperResponse = response
-            .json()
-            .await
-            .context("invalid whisper response")?;
+        let text = transcribe(ctx, self.name(), &self.openai_api_key, file_content, "de").await?;
 
-        tokio::fs::write(output_file, whisper.text)
+        ctx.ensure_not_cancelled()?;
+        tokio::fs::write(output_file, text)
             .await
             .context("failed to write transcription file")?;
 
@@ -1,51 +1,54 @@
-use crate::agents::agent::Agent;
 use crate::agents::context_relation::ContextRelationAgent;
 use crate::agents::person_relation::PersonRelationAgent;
+use crate::paths::record_dir_from_audio;
 use crate::pipeline::context::RecordContext;
+use crate::pipeline::error::PipelineError;
 use crate::pipeline::entities::EntityExtractionStep;
 use crate::pipeline::pipeline::Pipeline;
+use crate::pipeline::report::PipelineReport;
 use crate::pipeline::transcription::TranscriptionStep;
+use crate::processing::processing::ProcessingFile;
 
-pub async fn process_document(ctx: &RecordContext) -> Result<(), String> {
+/// Transcription and entity extraction are critical, the relation agents may fail on their own.
+/// Text sessions skip transcription, entity extraction then reads the imported text.txt.
+pub fn document_pipeline(with_audio: bool) -> Pipeline {
     let openai_api_key = std::env::var("OPENAI_API_KEY").unwrap_or_default();
 
-    ctx.emit("transcription", "Transkription gestartet", 5);
-
-    Pipeline::new()
-        .add_step(TranscriptionStep {
+    let mut pipeline = Pipeline::new();
+    if with_audio {
+        pipeline = pipeline.add_step(TranscriptionStep {
             openai_api_key: openai_api_key.clone(),
-        })
-        .run(ctx)
-        .await?;
-
-    ctx.emit("entities", "Entities extrahiert", 30);
+        });
+    }
 
-    Pipeline::new()
+    // entity extraction waits for the transcript, both relation agents only for the entities
+    pipeline
         .add_step(EntityExtractionStep {
             openai_api_key: openai_api_key.clone(),
+            prompt: None,
         })
-        .run(ctx)
-        .await?;
-
-    ctx.emit("relations", "Personenrelationen analysiert", 55);
-
-    PersonRelationAgent {
-        openai_api_key: openai_api_key.clone(),
-    }
-        .run_document(ctx)
-        .await
-        .map_err(|e| e.to_string())?;
-
-    ctx.emit("relations", "Kontextrelationen analysiert", 75);
+        .add_agent(PersonRelationAgent {
+            openai_api_key: openai_api_key.clone(),
+            prompt: None,
+        })
+        .add_agent(ContextRelationAgent {
+            openai_api_key,
+            prompt: None,
+        })
+}
 
-    ContextRelationAgent {
-        openai_api_key,
-    }
-        .run_document(ctx)
-        .await
-        .map_err(|e| e.to_string())?;
+/// `force` lists steps that run even if their outputs are up to date.
+pub async fn process_document(ctx: &RecordContext, force: &[String]) -> Result<PipelineReport, PipelineError> {
+    let record_dir = record_dir_from_audio(&ctx.base_dir, &ctx.audio_file).await?;
+    let processing = ProcessingFile::load_or_create(&record_dir, &ctx.audio_file).await?;
 
-    ctx.emit("done", "Verarbeitung abgeschlossen", 100);
+    run_document_pipeline(ctx, document_pipeline(processing.has_audio()).force(force)).await
+}
 
-    Ok(())
+/// Start and end of the job are emitted by the caller, the pipeline only reports its steps.
+pub async fn run_document_pipeline(
+    ctx: &RecordContext,
+    pipeline: Pipeline,
+) -> Result<PipelineReport, PipelineError> {
+    pipeline.run(ctx).await
 }
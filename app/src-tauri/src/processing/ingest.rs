@@ -0,0 +1,123 @@
+use anyhow::{Context, Result};
+use mail_parser::MessageParser;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+use crate::paths::{next_record_path, record_dir_from_audio};
+use crate::processing::processing::ProcessingFile;
+
+/// Source kind recorded in processing.json for an importable text file, None for anything else.
+pub fn text_kind(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_string_lossy().to_ascii_lowercase();
+    match extension.as_str() {
+        "txt" => Some("text"),
+        "md" | "markdown" => Some("markdown"),
+        "eml" => Some("email"),
+        _ => None,
+    }
+}
+
+/// Creates a session from a text file. The original is kept as data/YYYY/MM/recordNNNN.{ext},
+/// the extracted text goes to the record dir's text.txt. Returns the stored source path,
+/// which the queue and the pipeline use like an audio file.
+pub async fn ingest_text_file(data_root: &Path, path: &Path) -> Result<PathBuf> {
+    let kind = text_kind(path).with_context(|| format!("{} is no supported text file", path.display()))?;
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+
+    let raw = fs::read(path)
+        .await
+        .with_context(|| format!("failed to read {}", path.display()))?;
+
+    let text = match kind {
+        "email" => email_to_text(&raw)?,
+        _ => String::from_utf8_lossy(&raw).into_owned(),
+    };
+
+    create_session(data_root, &extension, &raw, &text, kind).await
+}
+
+/// Creates a session from text pasted into the app.
+pub async fn ingest_note(data_root: &Path, title: Option<&str>, content: &str) -> Result<PathBuf> {
+    let text = match title.map(str::trim).filter(|t| !t.is_empty()) {
+        Some(title) => format!("{}\n\n{}", title, content),
+        None => content.to_string(),
+    };
+
+    create_session(data_root, "txt", text.as_bytes(), &text, "note").await
+}
+
+async fn create_session(
+    data_root: &Path,
+    extension: &str,
+    original: &[u8],
+    text: &str,
+    kind: &str,
+) -> Result<PathBuf> {
+    if text.trim().is_empty() {
+        anyhow::bail!("document contains no text");
+    }
+
+    let source = next_record_path(data_root, extension)?;
+    fs::write(&source, original)
+        .await
+        .with_context(|| format!("failed to write {}", source.display()))?;
+
+    let base_dir = source.parent().context("source has no parent")?;
+    let record_dir = record_dir_from_audio(base_dir, &source).await?;
+
+    fs::write(record_dir.join("text.txt"), text)
+        .await
+        .context("failed to write text.txt")?;
+
+    let mut processing = ProcessingFile::load_or_create(&record_dir, &source).await?;
+    processing.source_kind = kind.into();
+    processing.save(&record_dir).await?;
+
+    println!("▶ ingest: {} session {} ({} chars)", kind, processing.doc_id, text.len());
+
+    Ok(source)
+}
+
+/// Subject, sender, recipients and date as a short header, followed by the plain text body.
+fn email_to_text(raw: &[u8]) -> Result<String> {
+    let message = MessageParser::default()
+        .parse(raw)
+        .context("invalid email")?;
+
+    let mut text = String::new();
+    if let Some(subject) = message.subject() {
+        text.push_str(&format!("Betreff: {}\n", subject));
+    }
+    if let Some(from) = message.from() {
+        text.push_str(&format!("Von: {}\n", format_addresses(from)));
+    }
+    if let Some(to) = message.to() {
+        text.push_str(&format!("An: {}\n", format_addresses(to)));
+    }
+    if let Some(date) = message.date() {
+        text.push_str(&format!("Datum: {}\n", date.to_rfc3339()));
+    }
+
+    let body = message.body_text(0).context("email has no text body")?;
+    text.push('\n');
+    text.push_str(body.trim());
+
+    Ok(text)
+}
+
+fn format_addresses(address: &mail_parser::Address) -> String {
+    address
+        .iter()
+        .map(|a| match (a.name(), a.address()) {
+            (Some(name), Some(addr)) => format!("{} <{}>", name, addr),
+            (Some(name), None) => name.to_string(),
+            (None, Some(addr)) => addr.to_string(),
+            (None, None) => String::new(),
+        })
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,249 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use walkdir::WalkDir;
+
+use crate::jobs::queue::JobQueue;
+use crate::pipeline::knowledge_builder::KnowledgeBuilder;
+use crate::processing::processing::ProcessingFile;
+use crate::processing::state_global::rebuild_global_state;
+use crate::store::evidence::EvidenceStore;
+use crate::store::knowledge::KnowledgeStore;
+use crate::store::lifecycle::Approver;
+
+/// Repaired files and record dirs are moved here instead of being deleted.
+const QUARANTINE_DIR: &str = "quarantine";
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    /// Record dir without processing.json and without a source file to process it from.
+    OrphanedRecordDir,
+    /// Audio session whose recording is gone, it cannot be transcribed again.
+    MissingAudio,
+    BrokenJson,
+    /// Global evidence of a document no session has anymore.
+    UnknownDocument,
+    /// Derived knowledge no evidence supports.
+    KnowledgeWithoutEvidence,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DataIssue {
+    pub kind: IssueKind,
+    pub path: Option<PathBuf>,
+    pub detail: String,
+    pub repairable: bool,
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct DataCheckReport {
+    pub sessions: usize,
+    pub issues: Vec<DataIssue>,
+    /// What repair mode did, empty without it.
+    pub repaired: Vec<String>,
+}
+
+impl DataCheckReport {
+    fn issue(&mut self, kind: IssueKind, path: Option<&Path>, detail: String, repairable: bool) {
+        println!("⚠ check_data: {:?} {}", kind, detail);
+        self.issues.push(DataIssue {
+            kind,
+            path: path.map(Path::to_path_buf),
+            detail,
+            repairable,
+        });
+    }
+}
+
+/// Scans the data root. With `repair`, broken JSON files and orphaned record dirs are moved to
+/// data/quarantine/{time}/ and signals, evidence and knowledge are rebuilt from the remaining sessions,
+/// which drops evidence of unknown documents and unsupported derived knowledge.
+/// Missing recordings and knowledge a user decided on are only reported. Repair is refused while jobs are open.
+pub async fn check_data(data_root: &Path, repair: bool) -> Result<DataCheckReport> {
+    println!("▶ check_data: {} (repair: {})", data_root.display(), repair);
+
+    let mut report = DataCheckReport::default();
+    let mut quarantine = Vec::new();
+
+    // broken JSON anywhere, the quarantine itself is left alone
+    for entry in WalkDir::new(data_root)
+        .into_iter()
+        .filter_entry(|e| e.path() != data_root.join(QUARANTINE_DIR))
+        .filter_map(Result::ok)
+    {
+        if !entry.file_type().is_file() || entry.path().extension().map_or(true, |e| e != "json") {
+            continue;
+        }
+        let raw = fs::read_to_string(entry.path()).await;
+        let broken = raw
+            .map_err(anyhow::Error::from)
+            .and_then(|raw| Ok(serde_json::from_str::<serde_json::Value>(&raw)?))
+            .err();
+        if let Some(err) = broken {
+            report.issue(
+                IssueKind::BrokenJson,
+                Some(entry.path()),
+                format!("{} → {}", entry.path().display(), err),
+                true,
+            );
+            quarantine.push(entry.path().to_path_buf());
+        }
+    }
+
+    // sessions: data/YYYY/MM/{stem}/
+    let mut doc_ids = HashSet::new();
+    for record_dir in record_dirs(data_root) {
+        let processing_path = record_dir.join("processing.json");
+        if !processing_path.exists() {
+            if !has_source_file(&record_dir).await {
+                report.issue(
+                    IssueKind::OrphanedRecordDir,
+                    Some(&record_dir),
+                    format!("{} has no processing.json and no source file", record_dir.display()),
+                    true,
+                );
+                quarantine.push(record_dir);
+            }
+            // otherwise not processed yet
+            continue;
+        }
+
+        // broken files were reported above
+        let Ok(processing) = ProcessingFile::load(&record_dir).await else {
+            continue;
+        };
+        report.sessions += 1;
+        doc_ids.insert(processing.doc_id.clone());
+
+        // audio removed by a retention policy is gone on purpose
+        let purged = processing.purged.iter().any(|p| p.kind == "audio");
+        if processing.has_audio() && !purged {
+            let audio = processing.source_path(&record_dir);
+            if !audio.exists() {
+                report.issue(
+                    IssueKind::MissingAudio,
+                    Some(&audio),
+                    format!("recording of {} is missing: {}", processing.doc_id, audio.display()),
+                    false,
+                );
+            }
+        }
+    }
+
+    let evidence = EvidenceStore::load_or_create(data_root).await.ok();
+    let mut supported = HashSet::new();
+    for record in evidence.iter().flat_map(|store| store.all()) {
+        supported.insert(KnowledgeBuilder::knowledge_id(record));
+        // confirmations only carry the confirmed record's documents along
+        if record.is_confirmation() {
+            continue;
+        }
+        for document in record.documents.iter().filter(|d| !doc_ids.contains(*d)) {
+            report.issue(
+                IssueKind::UnknownDocument,
+                None,
+                format!("evidence {} references unknown document {}", record.key, document),
+                true,
+            );
+        }
+    }
+
+    if let Ok(knowledge) = KnowledgeStore::load_or_create(data_root).await {
+        for record in knowledge.all() {
+            if record.source_agent != "KNOWLEDGE_BUILDER" || supported.contains(&record.id) {
+                continue;
+            }
+            report.issue(
+                IssueKind::KnowledgeWithoutEvidence,
+                None,
+                format!("knowledge {} ({}) has no evidence", record.id, record.status()),
+                record.approved_by() == Approver::None,
+            );
+        }
+    }
+
+    println!("▶ check_data: {} issues in {} sessions", report.issues.len(), report.sessions);
+
+    if repair && report.issues.iter().any(|i| i.repairable) {
+        // a running job writes into sessions and stores, nothing may move underneath it
+        JobQueue::while_idle(data_root, repair_data(data_root, &quarantine, &mut report)).await?;
+    }
+
+    Ok(report)
+}
+
+async fn repair_data(data_root: &Path, quarantine: &[PathBuf], report: &mut DataCheckReport) -> Result<()> {
+    let target_root = data_root
+        .join(QUARANTINE_DIR)
+        .join(Utc::now().format("%Y%m%dT%H%M%S").to_string());
+
+    for path in quarantine {
+        // may have been moved along with an earlier entry
+        if !path.exists() {
+            continue;
+        }
+        let target = target_root.join(path.strip_prefix(data_root).unwrap_or(path));
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        fs::rename(path, &target)
+            .await
+            .with_context(|| format!("failed to move {} to the quarantine", path.display()))?;
+        report
+            .repaired
+            .push(format!("moved {} → {}", path.display(), target.display()));
+    }
+
+    rebuild_global_state(data_root)
+        .await
+        .map_err(anyhow::Error::msg)?;
+    report
+        .repaired
+        .push("rebuilt signals, evidence and knowledge".into());
+
+    Ok(())
+}
+
+fn record_dirs(data_root: &Path) -> Vec<PathBuf> {
+    WalkDir::new(data_root)
+        .min_depth(3)
+        .max_depth(3)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|e| e.file_type().is_dir())
+        .filter(|e| {
+            let Ok(rel) = e.path().strip_prefix(data_root) else {
+                return false;
+            };
+            let parts: Vec<String> = rel
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .collect();
+            parts[0].len() == 4
+                && parts[1].len() == 2
+                && parts[0].chars().chain(parts[1].chars()).all(|c| c.is_ascii_digit())
+        })
+        .map(|e| e.into_path())
+        .collect()
+}
+
+/// recordNNNN.{ext} next to the record dir.
+async fn has_source_file(record_dir: &Path) -> bool {
+    let (Some(parent), Some(stem)) = (record_dir.parent(), record_dir.file_name()) else {
+        return false;
+    };
+    let Ok(mut entries) = fs::read_dir(parent).await else {
+        return false;
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let path = entry.path();
+        if path.is_file() && path.file_stem() == Some(stem) {
+            return true;
+        }
+    }
+    false
+}
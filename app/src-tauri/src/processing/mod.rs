@@ -4,3 +4,7 @@ pub mod processing;
 pub mod document;
 pub mod state_global;
 pub mod progress;
+pub mod reprocess;
+pub mod ingest;
+pub mod integrity;
+pub mod trash;
@@ -1,8 +1,51 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use crate::openai::retry::RequestAttempt;
+use crate::usage::model::UsageEntry;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ProcessingStep {
     pub status: String,
-    pub output: Option<String>,
+    pub output: Option<StepOutput>,
+    #[serde(default)]
+    pub started_at: Option<String>,
     pub finished_at: Option<String>,
+    #[serde(default)]
+    pub duration_ms: Option<u64>,
+}
+
+/// Everything a step reports about its own run, stored in processing.json.
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+pub struct StepOutput {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub usage: Vec<UsageEntry>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attempts: Vec<RequestAttempt>,
+    /// Files the step wrote, relative to the month dir (recordNNNN/entities.json).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub artifacts: Vec<String>,
+    /// e.g. entities, candidates, evidence_written
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub counts: BTreeMap<String, usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Prompt template as "name@version".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_version: Option<String>,
+    /// sha256 of every input at the time the step ran, keyed like `artifacts`.
+    /// A differing hash makes the step stale.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub input_hashes: BTreeMap<String, String>,
+}
+
+impl StepOutput {
+    pub fn is_empty(&self) -> bool {
+        self.usage.is_empty()
+            && self.attempts.is_empty()
+            && self.artifacts.is_empty()
+            && self.counts.is_empty()
+            && self.model.is_none()
+            && self.prompt_version.is_none()
+            && self.input_hashes.is_empty()
+    }
 }
@@ -1,28 +1,72 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use anyhow::{Result};
 use chrono::Utc;
-use tokio::fs;
 use uuid::Uuid;
+use crate::storage::{self, SessionRow};
+use crate::store::atomic::write_atomic;
+use crate::store::schema::{self, PROCESSING};
+use crate::usage::model::UsageEntry;
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ProcessingFile {
     pub doc_id: String,
+    /// Source file next to the record dir, a recording or an imported text document.
     pub audio_file: String,
+    /// audio | text | markdown | note | email
+    #[serde(default = "default_source_kind")]
+    pub source_kind: String,
+    #[serde(default)]
     pub started_at: String,
+    #[serde(default)]
     pub finished_at: Option<String>,
+    #[serde(default)]
     pub steps: HashMap<String, crate::processing::model::ProcessingStep>,
+    #[serde(default)]
     pub errors: Vec<String>,
+    /// Files removed by retention policies.
+    #[serde(default)]
+    pub purged: Vec<PurgedFile>,
+    /// Prompt A/B runs on this document. Billed, but not part of its processing.
+    #[serde(default)]
+    pub ab_tests: Vec<AbTestRun>,
 }
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PurgedFile {
+    pub file: String,
+    /// audio | transcript
+    pub kind: String,
+    pub purged_at: String,
+    /// e.g. "audio after 30 days"
+    pub policy: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AbTestRun {
+    pub prompt_id: String,
+    pub created_at: Option<String>,
+    pub usage: Vec<UsageEntry>,
+    pub error: Option<String>,
+}
+fn default_source_kind() -> String {
+    "audio".into()
+}
+
 impl ProcessingFile {
+    /// Text sessions start with text.txt, there is nothing to transcribe.
+    pub fn has_audio(&self) -> bool {
+        self.source_kind == "audio"
+    }
+
     pub async fn load(
         record_dir: &Path,
     ) -> Result<Self> {
         let path = record_dir.join("processing.json");
 
         if path.exists() {
-            let content = fs::read_to_string(&path).await?;
-            Ok(serde_json::from_str(&content)?)
+            let loaded = schema::load(&path, &PROCESSING).await?;
+            Ok(serde_json::from_value(loaded.value)?)
         } else {
             return Err(anyhow::anyhow!("Processing file not found"));
         }
@@ -34,24 +78,97 @@ impl ProcessingFile {
         let path = record_dir.join("processing.json");
 
         if path.exists() {
-            let content = fs::read_to_string(&path).await?;
-            Ok(serde_json::from_str(&content)?)
+            let loaded = schema::load(&path, &PROCESSING).await?;
+            Ok(serde_json::from_value(loaded.value)?)
         } else {
             Ok(Self {
                 doc_id: Uuid::new_v4().to_string(),
                 audio_file: audio_file.file_name().unwrap().to_string_lossy().to_string(),
+                source_kind: default_source_kind(),
                 started_at: Utc::now().to_rfc3339(),
                 finished_at: None,
                 steps: HashMap::new(),
                 errors: vec![],
+                purged: vec![],
+                ab_tests: vec![],
             })
         }
     }
 
+    /// Steps that failed because the last request attempt could not reach the network.
+    pub fn network_failures(&self) -> Vec<String> {
+        self.steps
+            .iter()
+            .filter(|(_, step)| step.status == "error")
+            .filter(|(_, step)| {
+                step.output
+                    .as_ref()
+                    .and_then(|o| o.attempts.last())
+                    .is_some_and(|a| a.is_network_error())
+            })
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// A step that did not finish successfully, or a logged error of a step that has not been done since.
+    pub fn has_errors(&self) -> bool {
+        self.steps.values().any(|s| s.status != "done")
+            || self.errors.iter().any(|e| {
+                let step = e.split_once(": ").map_or(e.as_str(), |(step, _)| step);
+                self.steps.get(step).map_or(true, |s| s.status != "done")
+            })
+    }
+
+    /// Drops the logged errors ("{step}: {error}") of a step that went through.
+    pub fn clear_step_errors(&mut self, step: &str) {
+        let prefix = format!("{}: ", step);
+        self.errors.retain(|e| !e.starts_with(&prefix));
+    }
+
+    /// Removed by a retention policy, matched by file name.
+    pub fn is_purged(&self, path: &Path) -> bool {
+        path.file_name()
+            .is_some_and(|name| self.purged.iter().any(|p| name.to_string_lossy() == p.file))
+    }
+
+    pub fn transcript_purged(&self) -> bool {
+        self.purged.iter().any(|p| p.kind == "transcript")
+    }
+
+    /// The recording or text document, usually stored by name next to the record dir.
+    pub fn source_path(&self, record_dir: &Path) -> PathBuf {
+        let source = Path::new(&self.audio_file);
+        if source.is_absolute() {
+            source.to_path_buf()
+        } else {
+            record_dir.parent().unwrap_or(record_dir).join(source)
+        }
+    }
+
+    pub fn session_row(&self, record_dir: &Path) -> SessionRow {
+        SessionRow {
+            doc_id: self.doc_id.clone(),
+            record_dir: record_dir.to_path_buf(),
+            source_kind: self.source_kind.clone(),
+            started_at: self.started_at.clone(),
+            finished_at: self.finished_at.clone(),
+            has_errors: self.has_errors(),
+        }
+    }
+
     pub async fn save(&self, record_dir: &Path) -> Result<()> {
         let path = record_dir.join("processing.json");
-        let json = serde_json::to_string_pretty(self)?;
-        fs::write(path, json).await?;
+        let json = schema::to_json(&PROCESSING, self)?;
+        write_atomic(&path, json.as_bytes()).await?;
+
+        // data/YYYY/MM/recordNNNN → data
+        if let Some(data_root) = record_dir.ancestors().nth(3) {
+            if let Some(db) = storage::mirror(data_root).await {
+                if let Err(err) = db.upsert_session(self.session_row(record_dir)).await {
+                    storage::mirror_failed(data_root, &err).await;
+                }
+            }
+        }
         Ok(())
     }
 }
@@ -1,14 +1,29 @@
-use tauri::{AppHandle, Emitter};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager};
 use serde::Serialize;
+use crate::jobs::manager::JobProgress;
+use crate::state::AppState;
 
 #[derive(Serialize, Clone)]
 pub struct ProgressEvent {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub job_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub doc_id: Option<String>,
     pub stage: String,
     pub message: String,
     pub percent: u8,
+    /// Set on per-step events of the pipeline: started | done | error | cancelled | skipped
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub step_status: Option<String>,
 }
 pub trait ProgressEmitter: Send + Sync {
     fn emit(&self, stage: &str, message: &str, percent: u8);
+
+    /// Start / finish of a single pipeline step.
+    fn emit_step(&self, step: &str, status: &str, percent: u8) {
+        self.emit(step, &format!("{} {}", step, status), percent);
+    }
 }
 pub fn emit_progress(
     app: &AppHandle,
@@ -19,9 +34,12 @@ pub fn emit_progress(
     let _ = app.emit(
         "processing:progress",
         ProgressEvent {
+            job_id: None,
+            doc_id: None,
             stage: stage.into(),
             message: message.into(),
             percent,
+            step_status: None,
         },
     );
 } 
@@ -38,13 +56,79 @@ impl TauriProgressEmitter {
 
 impl ProgressEmitter for TauriProgressEmitter {
     fn emit(&self, stage: &str, message: &str, percent: u8) {
-        let _ = self.app.emit(
-            "processing:progress",
-            ProgressEvent {
-                stage: stage.into(),
-                message: message.into(),
-                percent,
-            },
-        );
+        emit_progress(&self.app, stage, message, percent);
+    }
+}
+
+/// Tags every event with the job and document, and keeps the job manager's live state current.
+pub struct JobProgressEmitter {
+    app: AppHandle,
+    job_id: String,
+    doc_id: Option<String>,
+}
+
+impl JobProgressEmitter {
+    pub fn new(app: AppHandle, job_id: &str, doc_id: Option<String>) -> Self {
+        Self {
+            app,
+            job_id: job_id.into(),
+            doc_id,
+        }
+    }
+
+    fn send(&self, stage: &str, message: &str, percent: u8, step_status: Option<&str>) {
+        let event = ProgressEvent {
+            job_id: Some(self.job_id.clone()),
+            doc_id: self.doc_id.clone(),
+            stage: stage.into(),
+            message: message.into(),
+            percent,
+            step_status: step_status.map(Into::into),
+        };
+
+        self.app
+            .state::<AppState>()
+            .jobs
+            .report(&self.job_id, JobProgress::from(&event));
+
+        let _ = self.app.emit("processing:progress", event);
+    }
+}
+
+impl ProgressEmitter for JobProgressEmitter {
+    fn emit(&self, stage: &str, message: &str, percent: u8) {
+        self.send(stage, message, percent, None);
+    }
+
+    fn emit_step(&self, step: &str, status: &str, percent: u8) {
+        self.send(step, &format!("{} {}", step, status), percent, Some(status));
+    }
+}
+
+/// Maps the 0..100 of one phase of a job (document steps, global update) onto its share of the job.
+pub struct PhaseProgress {
+    inner: Arc<dyn ProgressEmitter>,
+    from: u8,
+    to: u8,
+}
+
+impl PhaseProgress {
+    pub fn new(inner: Arc<dyn ProgressEmitter>, from: u8, to: u8) -> Self {
+        Self { inner, from, to }
+    }
+
+    fn scale(&self, percent: u8) -> u8 {
+        let span = self.to.saturating_sub(self.from) as u16;
+        self.from + (span * percent.min(100) as u16 / 100) as u8
+    }
+}
+
+impl ProgressEmitter for PhaseProgress {
+    fn emit(&self, stage: &str, message: &str, percent: u8) {
+        self.inner.emit(stage, message, self.scale(percent));
+    }
+
+    fn emit_step(&self, step: &str, status: &str, percent: u8) {
+        self.inner.emit_step(step, status, self.scale(percent));
     }
 }
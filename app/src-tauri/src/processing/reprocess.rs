@@ -0,0 +1,133 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::jobs::queue::JobQueue;
+use crate::jobs::worker;
+use crate::processing::document::document_pipeline;
+use crate::processing::processing::ProcessingFile;
+
+/// Which sessions `reprocess_sessions` picks up.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SessionFilter {
+    All,
+    /// Inclusive, YYYY-MM-DD, compared against the session start.
+    DateRange { from: String, to: String },
+    /// Sessions with a failed step or a logged error.
+    WithErrors,
+    DocIds { doc_ids: Vec<String> },
+}
+
+impl SessionFilter {
+    fn matches(&self, processing: &ProcessingFile) -> bool {
+        match self {
+            Self::All => true,
+            Self::DateRange { from, to } => {
+                let day = processing.started_at.get(..10).unwrap_or_default();
+                day >= from.as_str() && day <= to.as_str()
+            }
+            Self::WithErrors => processing.has_errors(),
+            Self::DocIds { doc_ids } => doc_ids.contains(&processing.doc_id),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReprocessedSession {
+    pub doc_id: String,
+    /// The queued job, cancellable and reported like any other.
+    pub job_id: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReprocessSummary {
+    pub sessions: Vec<ReprocessedSession>,
+}
+
+/// Queues one job per matching session that reruns `steps` (all document steps if empty) and everything
+/// downstream. The worker runs them like new recordings, each job applies its document to the global state.
+/// Relation agents replace their earlier evidence, so it does not pile up on top of the old run.
+pub async fn reprocess_sessions(
+    data_root: &Path,
+    filter: &SessionFilter,
+    steps: &[String],
+) -> Result<ReprocessSummary> {
+    let known = document_pipeline(true).step_names();
+    let steps: Vec<String> = if steps.is_empty() {
+        known.iter().map(|s| s.to_string()).collect()
+    } else {
+        steps.to_vec()
+    };
+    if let Some(unknown) = steps.iter().find(|s| !known.contains(&s.as_str())) {
+        anyhow::bail!("unknown step {}", unknown);
+    }
+
+    let sessions = select_sessions(data_root, filter).await?;
+    println!(
+        "▶ reprocess_sessions: {} sessions, steps {}",
+        sessions.len(),
+        steps.join(", ")
+    );
+
+    let summary = JobQueue::update(data_root, |q| {
+        let sessions = sessions
+            .iter()
+            .map(|(record_dir, processing)| {
+                let job = q.enqueue_forced(&processing.source_path(record_dir), &steps);
+                if job.is_none() {
+                    println!("⚠ reprocess_sessions: {} is being processed, skipped", processing.doc_id);
+                }
+                ReprocessedSession {
+                    doc_id: processing.doc_id.clone(),
+                    error: job.is_none().then(|| "being processed right now".to_string()),
+                    job_id: job.map(|j| j.id),
+                }
+            })
+            .collect();
+        ReprocessSummary { sessions }
+    })
+    .await?;
+    worker::wake();
+
+    Ok(summary)
+}
+
+/// Record dirs (data/YYYY/MM/recordNNNN) with their processing.json, oldest first.
+async fn select_sessions(
+    data_root: &Path,
+    filter: &SessionFilter,
+) -> Result<Vec<(PathBuf, ProcessingFile)>> {
+    let mut sessions = Vec::new();
+
+    for entry in WalkDir::new(data_root)
+        .min_depth(4)
+        .max_depth(4)
+        .into_iter()
+        .filter_map(Result::ok)
+    {
+        if entry.file_name() != "processing.json" {
+            continue;
+        }
+        let Some(record_dir) = entry.path().parent() else {
+            continue;
+        };
+
+        let processing = match ProcessingFile::load(record_dir).await {
+            Ok(p) => p,
+            Err(err) => {
+                println!("⚠ reprocess_sessions: skipping {} → {}", record_dir.display(), err);
+                continue;
+            }
+        };
+
+        if filter.matches(&processing) {
+            sessions.push((record_dir.to_path_buf(), processing));
+        }
+    }
+
+    sessions.sort_by(|a, b| a.1.started_at.cmp(&b.1.started_at));
+    Ok(sessions)
+}
@@ -1,37 +1,144 @@
+use std::path::Path;
+
 use crate::pipeline::context::RecordContext;
 use crate::pipeline::evidences::EvidencesPipeline;
 use crate::pipeline::knowledge_builder::KnowledgeBuilder;
 use crate::pipeline::pipeline::Pipeline;
+use crate::pipeline::report::PipelineReport;
 use crate::pipeline::signals::SignalsPipeline;
+use crate::store::evidence::EvidenceStore;
+use crate::store::knowledge::KnowledgeStore;
+use crate::store::lifecycle::{Approver, KnowledgeStatus};
 use crate::resolvers::context::ResolverContext;
 use crate::resolvers::OrgIdentityResolver;
 use crate::resolvers::resolver::ResolverRunner;
+use crate::store::snapshot::create_snapshot;
 
-pub async fn update_global_state(ctx: &RecordContext) -> Result<(), String> {
-    Pipeline::new()
-        .add_step(SignalsPipeline)
-        .run(ctx)
-        .await?;
+/// Applies the processed document's changes to signals and evidence, then rebuilds knowledge.
+pub async fn update_global_state(ctx: &RecordContext) -> Result<PipelineReport, String> {
+    let data_root = ctx
+        .base_dir
+        .parent()
+        .and_then(|p| p.parent())
+        .ok_or("invalid data root")?
+        .to_path_buf();
 
-    Pipeline::new()
-        .add_step(EvidencesPipeline)
-        .run(ctx)
-        .await?;
+    snapshot_before(&data_root, "update_global_state").await;
 
-    Pipeline::new()
+    let report = Pipeline::new()
+        .add_step(SignalsPipeline)
+        .add_step(EvidencesPipeline)
         .add_step(KnowledgeBuilder)
         .run(ctx)
         .await?;
 
+    run_resolvers(&data_root).await?;
 
-    let data_root = ctx
-        .base_dir
-        .parent()
-        .and_then(|p| p.parent())
-        .ok_or("invalid data root")?
-        .to_path_buf();
+    Ok(report)
+}
 
-    let resolver_ctx = ResolverContext { data_root };
+/// Maintenance: rebuilds signals, evidence and knowledge from all sessions instead of applying deltas.
+pub async fn rebuild_global_state(data_root: &Path) -> Result<(), String> {
+    println!("▶ rebuild_global_state: {}", data_root.display());
+
+    snapshot_before(data_root, "rebuild_global_state").await;
+
+    SignalsPipeline::rebuild(data_root)
+        .await
+        .map_err(|e| e.to_string())?;
+    EvidencesPipeline::rebuild(data_root)
+        .await
+        .map_err(|e| e.to_string())?;
+    KnowledgeBuilder::build(data_root)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    run_resolvers(data_root).await
+}
+
+/// Takes a deleted document out of signals, evidence and knowledge. Derived knowledge without evidence
+/// is retracted by the builder; records that stay but had no other source document are deprecated
+/// by the system. Returns the ids of those records, restoring the document reopens them.
+pub async fn remove_document_from_global_state(data_root: &Path, doc_id: &str) -> Result<Vec<String>, String> {
+    println!("▶ remove_document_from_global_state: {}", doc_id);
+
+    snapshot_before(data_root, "delete_session").await;
+
+    let deprecated = remove_document(data_root, doc_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    run_resolvers(data_root).await?;
+    Ok(deprecated)
+}
+
+/// Undoes a delete: rebuilds with the restored session and reopens the records the delete deprecated,
+/// unless someone decided on them since.
+pub async fn restore_document_in_global_state(
+    data_root: &Path,
+    doc_id: &str,
+    deprecated: &[String],
+) -> Result<(), String> {
+    println!("▶ restore_document_in_global_state: {}", doc_id);
+
+    rebuild_global_state(data_root).await?;
+    if deprecated.is_empty() {
+        return Ok(());
+    }
+
+    reopen(data_root, doc_id, deprecated)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+async fn reopen(data_root: &Path, doc_id: &str, deprecated: &[String]) -> anyhow::Result<()> {
+    let mut knowledge = KnowledgeStore::lock_and_load(data_root).await?;
+    for id in deprecated {
+        let reopen = knowledge.get_by_id(id).is_some_and(|r| {
+            r.status() == KnowledgeStatus::Deprecated && r.approved_by() == Approver::System
+        });
+        if reopen {
+            knowledge.transition(id, KnowledgeStatus::Candidate, Approver::System)?;
+        }
+    }
+    knowledge.changed_by("system", &format!("restore session {}", doc_id));
+    knowledge.save().await
+}
+
+async fn remove_document(data_root: &Path, doc_id: &str) -> anyhow::Result<Vec<String>> {
+    SignalsPipeline::remove_document(data_root, doc_id).await?;
+
+    // without per-document contributions the share cannot be taken out, the session's files are gone already
+    if EvidenceStore::load_or_create(data_root).await?.has_untracked_evidence() {
+        EvidencesPipeline::rebuild(data_root).await?;
+    }
+    let mut evidence = EvidenceStore::lock_and_load(data_root).await?;
+    evidence.forget_document(doc_id);
+    evidence.save().await?;
+    drop(evidence);
+
+    KnowledgeBuilder::build(data_root).await?;
+
+    let mut knowledge = KnowledgeStore::lock_and_load(data_root).await?;
+    let mut deprecated = Vec::new();
+    for id in knowledge.remove_source_document(doc_id) {
+        let deprecate = knowledge
+            .get_by_id(&id)
+            .is_some_and(|r| r.status() != KnowledgeStatus::Deprecated);
+        if deprecate {
+            knowledge.transition(&id, KnowledgeStatus::Deprecated, Approver::System)?;
+            deprecated.push(id);
+        }
+    }
+    knowledge.changed_by("system", &format!("delete session {}", doc_id));
+    knowledge.save().await?;
+    Ok(deprecated)
+}
+
+async fn run_resolvers(data_root: &Path) -> Result<(), String> {
+    let resolver_ctx = ResolverContext {
+        data_root: data_root.to_path_buf(),
+    };
 
     ResolverRunner::run_all(
         &resolver_ctx,
@@ -42,7 +149,12 @@ pub async fn update_global_state(ctx: &RecordContext) -> Result<(), String> {
         ],
     )
         .await
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| e.to_string())
+}
 
-    Ok(())
-}
\ No newline at end of file
+/// A failed snapshot does not stop processing, the knowledge is just not restorable to this point.
+async fn snapshot_before(data_root: &Path, step: &str) {
+    if let Err(err) = create_snapshot(data_root, "auto", Some(step.to_string())).await {
+        println!("⚠ snapshot before {} failed → {}", step, err);
+    }
+}
@@ -0,0 +1,225 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+use crate::jobs::queue::JobQueue;
+use crate::processing::processing::ProcessingFile;
+use crate::processing::state_global::{remove_document_from_global_state, restore_document_in_global_state};
+use crate::processing::utils::find_record_dir;
+use crate::storage;
+use crate::store::schema::{self, TRASH};
+use crate::store::settings::Settings;
+
+const TRASH_FILE: &str = "trash.json";
+
+/// A deleted session in {app_data}/trash/{doc_id}/: the record dir as record/, the source file,
+/// and trash.json with this entry.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TrashEntry {
+    pub doc_id: String,
+    pub deleted_at: String,
+    /// Removed for good after this, see settings.trash.grace_days.
+    pub purge_after: String,
+    /// Original locations, restoring puts the files back there.
+    pub record_dir: PathBuf,
+    pub source_file: Option<PathBuf>,
+    /// Knowledge the delete deprecated, reopened on restore.
+    #[serde(default)]
+    pub deprecated_knowledge: Vec<String>,
+}
+
+/// Next to the data root, so nothing that scans the data root sees deleted sessions.
+fn trash_dir(data_root: &Path) -> PathBuf {
+    data_root.with_file_name("trash")
+}
+
+fn entry_dir(data_root: &Path, doc_id: &str) -> Result<PathBuf> {
+    anyhow::ensure!(
+        !doc_id.is_empty() && !doc_id.contains(['/', '\\', '.']),
+        "invalid document id {}",
+        doc_id
+    );
+    Ok(trash_dir(data_root).join(doc_id))
+}
+
+/// Moves the session's recording, transcript and per-document files to the trash and takes the
+/// document out of signals, evidence and knowledge. Queued sessions have to be cancelled first.
+pub async fn delete_session(data_root: &Path, doc_id: &str) -> Result<TrashEntry> {
+    println!("▶ delete_session {}", doc_id);
+
+    let dir = entry_dir(data_root, doc_id)?;
+    let record_dir = find_record_dir(data_root, doc_id).await?;
+    let processing = ProcessingFile::load(&record_dir).await?;
+
+    let source_file = Some(processing.source_path(&record_dir))
+        .filter(|p| !processing.audio_file.is_empty() && p.exists());
+
+    if let Some(source) = &source_file {
+        let queue = JobQueue::snapshot(data_root).await?;
+        let open = queue.jobs.iter().any(|j| {
+            &j.audio_file == source && matches!(j.status.as_str(), "pending" | "running" | "parked")
+        });
+        anyhow::ensure!(!open, "session {} is queued for processing, cancel it first", doc_id);
+    }
+
+    let grace_days = Settings::load_or_create(data_root).await?.trash.grace_days;
+    let now = Utc::now();
+    let mut entry = TrashEntry {
+        doc_id: doc_id.to_string(),
+        deleted_at: now.to_rfc3339(),
+        purge_after: (now + Duration::days(grace_days as i64)).to_rfc3339(),
+        record_dir: record_dir.clone(),
+        source_file: source_file.clone(),
+        deprecated_knowledge: Vec::new(),
+    };
+
+    fs::create_dir_all(&dir).await?;
+    write_entry(&dir, &entry).await?;
+    fs::rename(&record_dir, dir.join("record"))
+        .await
+        .with_context(|| format!("failed to move {} to the trash", record_dir.display()))?;
+    if let Some(source) = &source_file {
+        let name = source.file_name().context("source file has no name")?;
+        fs::rename(source, dir.join(name))
+            .await
+            .with_context(|| format!("failed to move {} to the trash", source.display()))?;
+    }
+
+    if let Some(db) = storage::mirror(data_root).await {
+        if let Err(err) = db.delete_session(doc_id).await {
+            storage::mirror_failed(data_root, &err).await;
+        }
+    }
+
+    entry.deprecated_knowledge = remove_document_from_global_state(data_root, doc_id)
+        .await
+        .map_err(anyhow::Error::msg)?;
+    write_entry(&dir, &entry).await?;
+
+    if let Err(err) = purge_expired(data_root).await {
+        println!("⚠ trash: purging failed → {}", err);
+    }
+
+    Ok(entry)
+}
+
+/// Newest first. Expired entries are purged before listing.
+pub async fn list_trash(data_root: &Path) -> Result<Vec<TrashEntry>> {
+    purge_expired(data_root).await?;
+
+    let mut entries: Vec<TrashEntry> = load_entries(data_root)
+        .await?
+        .into_iter()
+        .map(|(_, entry)| entry)
+        .collect();
+    entries.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at));
+    Ok(entries)
+}
+
+/// Puts the files back, rebuilds signals, evidence and knowledge with the session and reopens the
+/// knowledge the deletion deprecated.
+pub async fn restore_session(data_root: &Path, doc_id: &str) -> Result<()> {
+    println!("▶ restore_session {}", doc_id);
+
+    let dir = entry_dir(data_root, doc_id)?;
+    anyhow::ensure!(dir.join(TRASH_FILE).exists(), "session {} is not in the trash", doc_id);
+    let entry = load_entry(&dir).await?;
+
+    let inside = entry.record_dir.starts_with(data_root)
+        && entry.source_file.as_ref().map_or(true, |s| s.starts_with(data_root));
+    anyhow::ensure!(inside, "trash entry {} points outside the data directory", doc_id);
+    anyhow::ensure!(
+        !entry.record_dir.exists(),
+        "{} exists again, cannot restore",
+        entry.record_dir.display()
+    );
+    if let Some(source) = &entry.source_file {
+        anyhow::ensure!(!source.exists(), "{} exists again, cannot restore", source.display());
+    }
+
+    if let Some(parent) = entry.record_dir.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+    fs::rename(dir.join("record"), &entry.record_dir)
+        .await
+        .with_context(|| format!("failed to restore {}", entry.record_dir.display()))?;
+    if let Some(source) = &entry.source_file {
+        let name = source.file_name().context("source file has no name")?;
+        fs::rename(dir.join(name), source)
+            .await
+            .with_context(|| format!("failed to restore {}", source.display()))?;
+    }
+    fs::remove_dir_all(&dir).await?;
+
+    if let Some(db) = storage::mirror(data_root).await {
+        let processing = ProcessingFile::load(&entry.record_dir).await?;
+        if let Err(err) = db.upsert_session(processing.session_row(&entry.record_dir)).await {
+            storage::mirror_failed(data_root, &err).await;
+        }
+    }
+
+    restore_document_in_global_state(data_root, doc_id, &entry.deprecated_knowledge)
+        .await
+        .map_err(anyhow::Error::msg)
+}
+
+/// Removes every session in the trash for good, the grace period is skipped.
+pub async fn empty_trash(data_root: &Path) -> Result<usize> {
+    purge(data_root, |_| true).await
+}
+
+async fn purge_expired(data_root: &Path) -> Result<usize> {
+    let now = Utc::now();
+    purge(data_root, |entry| {
+        DateTime::parse_from_rfc3339(&entry.purge_after).is_ok_and(|at| at <= now)
+    })
+    .await
+}
+
+async fn purge(data_root: &Path, due: impl Fn(&TrashEntry) -> bool) -> Result<usize> {
+    let mut purged = 0;
+    for (dir, entry) in load_entries(data_root).await? {
+        if !due(&entry) {
+            continue;
+        }
+        fs::remove_dir_all(&dir)
+            .await
+            .with_context(|| format!("failed to purge {}", entry.doc_id))?;
+        println!("▶ trash: purged {}", entry.doc_id);
+        purged += 1;
+    }
+    Ok(purged)
+}
+
+async fn load_entries(data_root: &Path) -> Result<Vec<(PathBuf, TrashEntry)>> {
+    let dir = trash_dir(data_root);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut entries = Vec::new();
+    let mut dirs = fs::read_dir(&dir).await?;
+    while let Some(item) = dirs.next_entry().await? {
+        if !item.path().join(TRASH_FILE).exists() {
+            continue;
+        }
+        match load_entry(&item.path()).await {
+            Ok(entry) => entries.push((item.path(), entry)),
+            Err(err) => println!("⚠ trash: skipping {} → {}", item.path().display(), err),
+        }
+    }
+    Ok(entries)
+}
+
+async fn write_entry(dir: &Path, entry: &TrashEntry) -> Result<()> {
+    fs::write(dir.join(TRASH_FILE), schema::to_json(&TRASH, entry)?)
+        .await
+        .context("failed to write trash.json")
+}
+
+async fn load_entry(dir: &Path) -> Result<TrashEntry> {
+    let loaded = schema::load(&dir.join(TRASH_FILE), &TRASH).await?;
+    serde_json::from_value(loaded.value).context("invalid trash.json")
+}
@@ -1,5 +1,7 @@
 use anyhow::{Context, Result};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use sha2::{Digest, Sha256};
+use walkdir::WalkDir;
 pub async fn load_document_id(record_dir: &Path) -> Result<String> {
     let path = record_dir.join("processing.json");
 
@@ -16,3 +18,33 @@ pub async fn load_document_id(record_dir: &Path) -> Result<String> {
 
     Ok(doc_id.to_string())
 }
+
+/// Finds the record dir whose processing.json carries `doc_id`.
+pub async fn find_record_dir(data_root: &Path, doc_id: &str) -> Result<PathBuf> {
+    for entry in WalkDir::new(data_root)
+        .into_iter()
+        .filter_map(|e| e.ok())
+    {
+        if entry.file_name() != "processing.json" {
+            continue;
+        }
+        let Some(record_dir) = entry.path().parent() else {
+            continue;
+        };
+        if let Ok(id) = load_document_id(record_dir).await {
+            if id == doc_id {
+                return Ok(record_dir.to_path_buf());
+            }
+        }
+    }
+
+    anyhow::bail!("no session with doc id {}", doc_id)
+}
+
+/// Hex encoded sha256 of a file's content.
+pub async fn sha256_file(path: &Path) -> Result<String> {
+    let bytes = tokio::fs::read(path)
+        .await
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    Ok(format!("{:x}", Sha256::digest(&bytes)))
+}
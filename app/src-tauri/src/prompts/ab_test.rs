@@ -0,0 +1,205 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::Value;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::fs;
+use uuid::Uuid;
+
+use crate::agents::context_relation::ContextRelationAgent;
+use crate::agents::person_relation::PersonRelationAgent;
+use crate::pipeline::context::RecordContext;
+use crate::pipeline::entities::EntityExtractionStep;
+use crate::pipeline::pipeline::Pipeline;
+use crate::processing::processing::{AbTestRun, ProcessingFile};
+use crate::processing::utils::find_record_dir;
+use crate::prompts::builtin::{CONTEXT_RELATION, ENTITY_EXTRACTION, PERSON_RELATION};
+use crate::prompts::registry::PromptRegistry;
+use crate::prompts::template::PromptTemplate;
+use crate::redaction::redactor::Redactor;
+use crate::store::schema::{self, AB_TEST};
+use crate::usage::model::UsageEntry;
+
+#[derive(Debug, Serialize)]
+pub struct AbVariant {
+    pub prompt_id: String,
+    /// entities.json (entity-extraction) or evidence.json (relation agents) of the run
+    pub output: Value,
+    pub usage: Vec<UsageEntry>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AbTestResult {
+    pub doc_id: String,
+    pub prompt: String,
+    pub created_at: String,
+    pub a: AbVariant,
+    pub b: AbVariant,
+}
+
+/// Runs the step behind `prompt` twice on the same document, once per version.
+/// Runs happen on a scratch copy of the record dir, so the session and all stores stay untouched.
+/// The comparison is kept as ab-{prompt}-{a}-vs-{b}.json in the record dir.
+pub async fn run_ab_test(
+    data_root: &Path,
+    doc_id: &str,
+    prompt: &str,
+    version_a: &str,
+    version_b: &str,
+) -> Result<AbTestResult> {
+    let record_dir = find_record_dir(data_root, doc_id).await?;
+    let registry = PromptRegistry::load(data_root).await?;
+
+    let template_a = registry
+        .get(prompt, version_a)
+        .with_context(|| format!("prompt {}@{} not found", prompt, version_a))?
+        .clone();
+    let template_b = registry
+        .get(prompt, version_b)
+        .with_context(|| format!("prompt {}@{} not found", prompt, version_b))?
+        .clone();
+
+    println!(
+        "▶ ab_test {} on {}: {} vs {}",
+        prompt,
+        doc_id,
+        template_a.id(),
+        template_b.id()
+    );
+
+    let redactor = Arc::new(Redactor::load(data_root).await?);
+    let a = run_variant(&record_dir, template_a, redactor.clone()).await?;
+    let b = run_variant(&record_dir, template_b, redactor).await?;
+
+    let result = AbTestResult {
+        doc_id: doc_id.into(),
+        prompt: prompt.into(),
+        created_at: Utc::now().to_rfc3339(),
+        a,
+        b,
+    };
+
+    // A/B calls are billed too, keep them visible to the usage report and the budget
+    let mut processing = ProcessingFile::load(&record_dir).await?;
+    for variant in [&result.a, &result.b] {
+        processing.ab_tests.push(AbTestRun {
+            prompt_id: variant.prompt_id.clone(),
+            created_at: Some(result.created_at.clone()),
+            usage: variant.usage.clone(),
+            error: variant.error.clone(),
+        });
+    }
+    processing.save(&record_dir).await?;
+
+    let report_path = record_dir.join(format!("ab-{}-{}-vs-{}.json", prompt, version_a, version_b));
+    fs::write(&report_path, schema::to_json(&AB_TEST, &result)?)
+        .await
+        .with_context(|| format!("failed to write {}", report_path.display()))?;
+
+    Ok(result)
+}
+
+async fn run_variant(
+    record_dir: &Path,
+    template: PromptTemplate,
+    redactor: Arc<Redactor>,
+) -> Result<AbVariant> {
+    let openai_api_key = std::env::var("OPENAI_API_KEY").unwrap_or_default();
+    let stem = record_dir
+        .file_name()
+        .context("record dir has no name")?
+        .to_string_lossy()
+        .to_string();
+
+    let scratch = std::env::temp_dir()
+        .join("via-ab")
+        .join(Uuid::new_v4().to_string());
+    let scratch_record_dir = scratch.join(&stem);
+    fs::create_dir_all(&scratch_record_dir).await?;
+
+    // inputs only, outputs of the real run must not leak into the comparison
+    for file in ["processing.json", "text.txt", "entities.json"] {
+        let src = record_dir.join(file);
+        if src.exists() {
+            fs::copy(&src, scratch_record_dir.join(file)).await?;
+        }
+    }
+
+    // start from a clean log so only this run's step entry and errors are read back
+    let mut processing = ProcessingFile::load(&scratch_record_dir).await?;
+    processing.steps.clear();
+    processing.errors.clear();
+    processing.ab_tests.clear();
+    processing.save(&scratch_record_dir).await?;
+
+    let ctx = RecordContext {
+        base_dir: scratch.clone(),
+        audio_file: scratch.join(format!("{}.wav", stem)),
+        redactor: Some(redactor),
+        ..Default::default()
+    };
+
+    let prompt_id = template.id();
+    let (pipeline, step_name, output_file) = match template.name.as_str() {
+        ENTITY_EXTRACTION => (
+            Pipeline::new().add_step(EntityExtractionStep {
+                openai_api_key,
+                prompt: Some(template),
+            }),
+            "entity-extraction",
+            "entities.json",
+        ),
+        PERSON_RELATION => (
+            Pipeline::new().add_agent(PersonRelationAgent {
+                openai_api_key,
+                prompt: Some(template),
+            }),
+            "PERSON_RELATION_AGENT",
+            "evidence.json",
+        ),
+        CONTEXT_RELATION => (
+            Pipeline::new().add_agent(ContextRelationAgent {
+                openai_api_key,
+                prompt: Some(template),
+            }),
+            "CONTEXT_RELATION_AGENT",
+            "evidence.json",
+        ),
+        other => anyhow::bail!("no step uses prompt {}", other),
+    };
+
+    let run = pipeline.run(&ctx).await;
+
+    let output_path = scratch_record_dir.join(output_file);
+    let output = if output_path.exists() {
+        serde_json::from_str(&fs::read_to_string(&output_path).await?)?
+    } else {
+        Value::Null
+    };
+
+    let processing = ProcessingFile::load(&scratch_record_dir).await?;
+    let step = processing.steps.get(step_name);
+    let usage = step
+        .and_then(|s| s.output.as_ref())
+        .map(|o| o.usage.clone())
+        .unwrap_or_default();
+    let error = match run {
+        Err(err) => Some(err.to_string()),
+        Ok(report) => report
+            .steps
+            .into_iter()
+            .find(|s| s.name == step_name)
+            .and_then(|s| s.error),
+    };
+
+    let _ = fs::remove_dir_all(&scratch).await;
+
+    Ok(AbVariant {
+        prompt_id,
+        output,
+        usage,
+        error,
+    })
+}
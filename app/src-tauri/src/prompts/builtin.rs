@@ -0,0 +1,102 @@
+use super::template::PromptTemplate;
+
+pub const ENTITY_EXTRACTION: &str = "entity-extraction";
+pub const PERSON_RELATION: &str = "person-relation";
+pub const CONTEXT_RELATION: &str = "context-relation";
+
+pub const BUILTIN_VERSION: &str = "v1";
+
+const ENTITY_EXTRACTION_SYSTEM: &str = "You extract named entities from text.
+The document is a source, not the truth.
+Do not infer or assume.
+Only extract what is explicitly present.
+Return JSON only.";
+
+const ENTITY_EXTRACTION_USER: &str = "Extract entities from the following document:
+
+{DOCUMENT_TEXT}";
+
+const PERSON_RELATION_USER: &str = r#"You are analyzing a single document.
+
+The document is a source, not the truth.
+Do not assume roles.
+Do not decide facts.
+Only propose relations that are explicitly stated in the document text.
+If there is no explicit statement linking a person to an organization, return an empty list.
+
+Document text:
+---
+{DOCUMENT_TEXT}
+---
+
+Persons found (extracted):
+{PERSONS_JSON}
+
+Organizations found (extracted):
+{ORGANIZATIONS_JSON}
+
+Task:
+- Identify which persons are explicitly related to which organizations in THIS document
+- Do not invent relations
+- If unsure, return an empty list
+
+Return JSON only (no markdown):
+[
+  {
+    "person": "<name>",
+    "organization": "<name>",
+    "confidence": 0.0-1.0
+  }
+]
+"#;
+
+const CONTEXT_RELATION_USER: &str = r#"
+You are analyzing a single document.
+
+The document is a source, not the truth.
+Do not infer intent, roles, or importance.
+Do not generalize.
+Only extract relationships that are explicitly stated in the text.
+
+Document text:
+---
+{DOCUMENT_TEXT}
+---
+
+Extracted entities (with type):
+{ENTITIES_JSON}
+
+Task:
+- Identify explicit relationships between any two entities mentioned in the document
+- Only if the relationship is clearly stated in the text
+- If no explicit relationship is stated, return an empty list
+
+Return JSON only (no markdown):
+
+[
+  {
+    "from_type": "<entity_type>",
+    "from": "<entity_text>",
+    "to_type": "<entity_type>",
+    "to": "<entity_text>",
+    "confidence": 0.0-1.0
+  }
+]
+"#;
+
+/// The prompts compiled into the app, always available as version v1.
+pub fn builtin_templates() -> Vec<PromptTemplate> {
+    let builtin = |name: &str, system: Option<&str>, user: &str| PromptTemplate {
+        name: name.into(),
+        version: BUILTIN_VERSION.into(),
+        system: system.map(Into::into),
+        user: user.into(),
+        source: "builtin".into(),
+    };
+
+    vec![
+        builtin(ENTITY_EXTRACTION, Some(ENTITY_EXTRACTION_SYSTEM), ENTITY_EXTRACTION_USER),
+        builtin(PERSON_RELATION, None, PERSON_RELATION_USER),
+        builtin(CONTEXT_RELATION, None, CONTEXT_RELATION_USER),
+    ]
+}
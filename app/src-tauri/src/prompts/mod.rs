@@ -0,0 +1,4 @@
+pub mod ab_test;
+pub mod builtin;
+pub mod registry;
+pub mod template;
@@ -0,0 +1,110 @@
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::path::Path;
+use tokio::fs;
+
+use super::builtin::{builtin_templates, BUILTIN_VERSION};
+use super::template::PromptTemplate;
+use crate::pipeline::context::RecordContext;
+use crate::store::settings::Settings;
+
+/// All known prompt templates: the built-ins plus
+/// {data_root}/prompts/{name}/{version}.txt files.
+pub struct PromptRegistry {
+    templates: BTreeMap<(String, String), PromptTemplate>,
+    active: BTreeMap<String, String>,
+}
+
+impl PromptRegistry {
+    pub async fn load(data_root: &Path) -> Result<Self> {
+        let mut templates = BTreeMap::new();
+        for t in builtin_templates() {
+            templates.insert((t.name.clone(), t.version.clone()), t);
+        }
+
+        let prompts_dir = data_root.join("prompts");
+        if prompts_dir.exists() {
+            let mut names = fs::read_dir(&prompts_dir).await?;
+            while let Some(name_entry) = names.next_entry().await? {
+                if !name_entry.file_type().await?.is_dir() {
+                    continue;
+                }
+                let name = name_entry.file_name().to_string_lossy().to_string();
+
+                let mut versions = fs::read_dir(name_entry.path()).await?;
+                while let Some(version_entry) = versions.next_entry().await? {
+                    let path = version_entry.path();
+                    if path.extension().and_then(|e| e.to_str()) != Some("txt") {
+                        continue;
+                    }
+                    let Some(version) = path.file_stem().and_then(|s| s.to_str()) else {
+                        continue;
+                    };
+
+                    // built-ins are the fixed reference, a file must not silently change them
+                    if version == BUILTIN_VERSION {
+                        println!(
+                            "⚠ prompt file {} ignored, {} is reserved for the built-in prompt",
+                            path.display(),
+                            BUILTIN_VERSION
+                        );
+                        continue;
+                    }
+
+                    let raw = fs::read_to_string(&path)
+                        .await
+                        .with_context(|| format!("failed to read {}", path.display()))?;
+
+                    let template = PromptTemplate::parse(
+                        &name,
+                        version,
+                        &raw,
+                        path.display().to_string(),
+                    );
+                    templates.insert((name.clone(), version.to_string()), template);
+                }
+            }
+        }
+
+        let settings = Settings::load_or_create(data_root).await?;
+
+        Ok(Self {
+            templates,
+            active: settings.prompts.active,
+        })
+    }
+
+    pub fn get(&self, name: &str, version: &str) -> Option<&PromptTemplate> {
+        self.templates.get(&(name.to_string(), version.to_string()))
+    }
+
+    /// The version selected in settings, falling back to the built-in.
+    pub fn active(&self, name: &str) -> Result<&PromptTemplate> {
+        let version = self
+            .active
+            .get(name)
+            .map(String::as_str)
+            .unwrap_or(BUILTIN_VERSION);
+
+        self.get(name, version)
+            .with_context(|| format!("prompt {}@{} not found", name, version))
+    }
+
+    pub fn all(&self) -> Vec<&PromptTemplate> {
+        self.templates.values().collect()
+    }
+}
+
+/// The preset template if a caller pinned one, otherwise the active version from the registry.
+pub async fn resolve_prompt(
+    ctx: &RecordContext,
+    name: &str,
+    preset: &Option<PromptTemplate>,
+) -> Result<PromptTemplate> {
+    if let Some(template) = preset {
+        return Ok(template.clone());
+    }
+
+    let registry = PromptRegistry::load(&ctx.data_root()?).await?;
+    Ok(registry.active(name)?.clone())
+}
@@ -0,0 +1,104 @@
+use serde::Serialize;
+
+use crate::openai::chat::ChatMessage;
+
+/// A named, versioned prompt. Placeholders look like `{DOCUMENT_TEXT}`.
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptTemplate {
+    pub name: String,
+    pub version: String,
+    pub system: Option<String>,
+    pub user: String,
+    /// builtin | file path the template was loaded from
+    pub source: String,
+}
+
+impl PromptTemplate {
+    /// "name@version", the value stored on evidence records.
+    pub fn id(&self) -> String {
+        format!("{}@{}", self.name, self.version)
+    }
+
+    /// Parses a template file. `[system]` and `[user]` lines start sections,
+    /// a file without markers is a user prompt only.
+    pub fn parse(name: &str, version: &str, raw: &str, source: String) -> Self {
+        let mut system: Option<String> = None;
+        let mut user: Option<String> = None;
+        let mut current: Option<&str> = None;
+        let mut buffer = String::new();
+
+        let mut flush = |section: Option<&str>, buffer: &mut String| {
+            let text = buffer.trim().to_string();
+            buffer.clear();
+            match section {
+                Some("system") => system = Some(text),
+                Some("user") => user = Some(text),
+                _ if !text.is_empty() => user = Some(text),
+                _ => {}
+            }
+        };
+
+        for line in raw.lines() {
+            match line.trim() {
+                "[system]" | "[user]" => {
+                    flush(current, &mut buffer);
+                    current = Some(if line.trim() == "[system]" { "system" } else { "user" });
+                }
+                _ => {
+                    buffer.push_str(line);
+                    buffer.push('\n');
+                }
+            }
+        }
+        flush(current, &mut buffer);
+
+        Self {
+            name: name.into(),
+            version: version.into(),
+            system,
+            user: user.unwrap_or_default(),
+            source,
+        }
+    }
+
+    /// Renders the template into chat messages.
+    pub fn render(&self, vars: &[(&str, &str)]) -> Vec<ChatMessage> {
+        let mut messages = Vec::new();
+        if let Some(system) = &self.system {
+            messages.push(ChatMessage::system(fill(system, vars)));
+        }
+        messages.push(ChatMessage::user(fill(&self.user, vars)));
+        messages
+    }
+}
+
+/// Single pass substitution, so placeholder-like text inside inserted values stays untouched.
+fn fill(template: &str, vars: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let tail = &rest[start..];
+
+        let value = tail.find('}').and_then(|end| {
+            let key = &tail[1..end];
+            vars.iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| (*v, end))
+        });
+
+        match value {
+            Some((v, end)) => {
+                out.push_str(v);
+                rest = &tail[end + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = &tail[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
@@ -0,0 +1,174 @@
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::store::knowledge::KnowledgeStore;
+use crate::store::lifecycle::EntityType;
+use crate::store::settings::{RedactionSettings, Settings};
+
+const EMAIL: &str = r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}";
+const IBAN: &str = r"\b[A-Z]{2}\d{2}(?: ?[A-Z0-9]{4}){2,7}(?: ?[A-Z0-9]{1,4})?\b";
+/// +49 30 1234567, +49 (0)30 1234567, (030) 123456, 0171/1234567, 030 123 456 78: a country code or a
+/// leading 0, then at least six digits with single spaces, slashes or hyphens in between. Dots never
+/// separate, so dates (01.02.2024) and amounts stay, and short ranges (0800-1200) have too few digits.
+const PHONE: &str = r"(?:\+\d{1,3}(?: ?\(0\))?|\(0\d{2,4}\) ?|\b0\d{2,4})(?:[ /-]?\d){6,}\b";
+const HEALTH: &str = r"(?i)\b(?:diagnos\w*|krankheit\w*|erkrank\w*|depressi\w*|diabet\w*|krebs\w*|cancer|tumor\w*|therapie\w*|therap(?:y|ist)\w*|medikament\w*|medicat\w*|schwanger\w*|pregnan\w*|hiv|burn-?out|psychiat\w*|psycholog\w*)\b";
+
+/// Names shorter than this are too ambiguous to redact as words.
+const MIN_NAME_LEN: usize = 3;
+
+/// Replaces sensitive spans with placeholders like `[EMAIL_1]`.
+/// IBANs run before phone numbers, their digit groups would otherwise look like one.
+pub struct Redactor {
+    rules: Vec<(String, Regex)>,
+}
+
+/// Placeholder mapping of one LLM call, used to put the original values back.
+#[derive(Debug, Default)]
+pub struct Redaction {
+    by_original: HashMap<(String, String), String>,
+    placeholders: Vec<(String, String)>,
+    counters: HashMap<String, usize>,
+}
+
+impl Redactor {
+    /// Builds the redactor from settings.json and the person names in knowledge.json.
+    pub async fn load(data_root: &Path) -> Result<Self> {
+        let settings = Settings::load_or_create(data_root).await?;
+
+        let mut names = settings.redaction.extra_names.clone();
+        if settings.redaction.enabled && settings.redaction.known_names {
+            let knowledge = KnowledgeStore::load_or_create(data_root).await?;
+            for record in knowledge.all() {
+                if record.subject_type == EntityType::Person {
+                    names.push(record.subject_value.clone());
+                }
+            }
+        }
+
+        Self::new(&settings.redaction, names)
+    }
+
+    pub fn new(settings: &RedactionSettings, names: Vec<String>) -> Result<Self> {
+        let mut rules = Vec::new();
+        if !settings.enabled {
+            return Ok(Self { rules });
+        }
+
+        if settings.emails {
+            rules.push(("EMAIL".to_string(), Regex::new(EMAIL)?));
+        }
+        if settings.ibans {
+            rules.push(("IBAN".to_string(), Regex::new(IBAN)?));
+        }
+        if settings.phone_numbers {
+            rules.push(("PHONE".to_string(), Regex::new(PHONE)?));
+        }
+        if settings.health_terms {
+            rules.push(("HEALTH".to_string(), Regex::new(HEALTH)?));
+        }
+        for custom in &settings.custom_patterns {
+            let regex = Regex::new(&custom.pattern)
+                .with_context(|| format!("invalid redaction pattern {}", custom.label))?;
+            rules.push((custom.label.to_uppercase(), regex));
+        }
+
+        let mut seen = HashSet::new();
+        let mut names: Vec<String> = names
+            .into_iter()
+            .map(|n| n.trim().to_string())
+            .filter(|n| n.chars().count() >= MIN_NAME_LEN)
+            .filter(|n| seen.insert(n.to_lowercase()))
+            .collect();
+        names.sort_by_key(|n| std::cmp::Reverse(n.len()));
+
+        if !names.is_empty() {
+            // longest first, so "Anna Maria Schmidt" wins over "Anna"
+            let alternation = names
+                .iter()
+                .map(|n| regex::escape(n))
+                .collect::<Vec<_>>()
+                .join("|");
+            rules.push(("NAME".to_string(), Regex::new(&format!(r"(?i)\b(?:{})\b", alternation))?));
+        }
+
+        Ok(Self { rules })
+    }
+
+    /// Redaction with the default rules and no names, for callers without a loaded redactor.
+    pub fn defaults() -> Self {
+        Self::new(&RedactionSettings::default(), Vec::new())
+            .expect("built-in redaction patterns are valid")
+    }
+
+    pub fn redact(&self, text: &str, redaction: &mut Redaction) -> String {
+        let mut out = text.to_string();
+        for (label, regex) in &self.rules {
+            out = regex
+                .replace_all(&out, |caps: &regex::Captures| redaction.placeholder(label, &caps[0]))
+                .into_owned();
+        }
+        out
+    }
+}
+
+impl Redaction {
+    /// Same original value, same placeholder.
+    fn placeholder(&mut self, label: &str, original: &str) -> String {
+        let key = (label.to_string(), original.to_lowercase());
+        if let Some(existing) = self.by_original.get(&key) {
+            return existing.clone();
+        }
+
+        let counter = self.counters.entry(label.to_string()).or_insert(0);
+        *counter += 1;
+        let placeholder = format!("[{}_{}]", label, counter);
+
+        self.by_original.insert(key, placeholder.clone());
+        self.placeholders.push((placeholder.clone(), original.to_string()));
+        placeholder
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.placeholders.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.placeholders.len()
+    }
+
+    /// Puts the original values back into a JSON answer.
+    /// Values are inserted JSON-escaped, since placeholders sit inside JSON strings.
+    pub fn restore_json(&self, text: &str) -> String {
+        let mut out = text.to_string();
+        for (placeholder, original) in &self.placeholders {
+            let quoted = serde_json::Value::String(original.clone()).to_string();
+            out = out.replace(placeholder.as_str(), &quoted[1..quoted.len() - 1]);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redact(text: &str) -> String {
+        Redactor::defaults().redact(text, &mut Redaction::default())
+    }
+
+    #[test]
+    fn phone_numbers_are_redacted() {
+        for phone in ["+49 30 1234567", "+49 (0)30 1234567", "(030) 123456", "0171/1234567", "030 123 456 78"] {
+            assert_eq!(redact(&format!("Tel. {}", phone)), "Tel. [PHONE_1]", "{}", phone);
+        }
+    }
+
+    #[test]
+    fn dates_and_ranges_are_not_phone_numbers() {
+        for text in ["am 01.02.2024 um 10 Uhr", "Termin 2024-01-02", "von 0800-1200", "Seiten 010-050"] {
+            assert_eq!(redact(text), text);
+        }
+    }
+}
@@ -4,6 +4,7 @@ use std::time::Instant;
 use crate::resolvers::context::ResolverContext;
 use crate::resolvers::resolver::Resolver;
 use crate::store::knowledge::KnowledgeStore;
+use crate::store::lifecycle::{EntityType, KnowledgeStatus};
 
 pub struct OrgIdentityResolver;
 
@@ -16,12 +17,13 @@ impl Resolver for OrgIdentityResolver {
     async fn run(&self, ctx: &ResolverContext) -> Result<()> {
         println!("▶ ResolverRunner: start {}", self.name());
         let started = Instant::now();
-        let mut knowledge = KnowledgeStore::load_or_create(&ctx.data_root).await?;
+        let mut knowledge = KnowledgeStore::lock_and_load(&ctx.data_root).await?;
 
         // 2. apply resolver logic
         Self::resolve(&mut knowledge)?;
 
         // 3. persist
+        knowledge.changed_by(self.name(), "organization clusters");
         knowledge.save().await?;
 
         println!(
@@ -52,7 +54,7 @@ impl OrgIdentityResolver {
 
         for record in knowledge.all() {
             // subject kann organization sein
-            if record.subject_type == "organization" {
+            if record.subject_type == EntityType::Organization {
                 let norm = normalize_org_name(&record.subject_value);
                 org_variants
                     .entry(norm)
@@ -94,7 +96,7 @@ impl OrgIdentityResolver {
                 normalized,
                 variants: variants_vec,
                 confidence: 0.9, // heuristisch für v1
-                status: "candidate".into(),
+                status: KnowledgeStatus::Candidate,
                 source_agent: "ORG_IDENTITY_RESOLVER".into(),
                 created_at: now.clone(),
                 updated_at: None,
@@ -1,6 +1,8 @@
 use std::sync::Mutex;
+use crate::jobs::manager::JobManager;
 use crate::recording::Recording;
 
 pub struct AppState {
     pub recorder: Mutex<Recording>,
+    pub jobs: JobManager,
 }
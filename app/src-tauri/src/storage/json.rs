@@ -0,0 +1,75 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use super::{EvidenceQuery, KnowledgeQuery, SessionRow, Storage};
+use crate::processing::processing::ProcessingFile;
+use crate::store::evidence::{EvidenceRecord, EvidenceStore};
+use crate::store::knowledge::{KnowledgeRecord, KnowledgeStore};
+
+/// Queries the JSON files directly, loading them on every call.
+pub struct JsonStorage {
+    data_root: PathBuf,
+}
+
+impl JsonStorage {
+    pub fn new(data_root: &Path) -> Self {
+        Self {
+            data_root: data_root.to_path_buf(),
+        }
+    }
+}
+
+#[async_trait]
+impl Storage for JsonStorage {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+
+    async fn knowledge(&self, query: &KnowledgeQuery) -> Result<Vec<KnowledgeRecord>> {
+        let store = KnowledgeStore::load_or_create(&self.data_root).await?;
+        Ok(store
+            .all()
+            .into_iter()
+            .filter(|r| query.matches(r))
+            .cloned()
+            .collect())
+    }
+
+    async fn evidence(&self, query: &EvidenceQuery) -> Result<Vec<EvidenceRecord>> {
+        let store = EvidenceStore::load_or_create(&self.data_root).await?;
+        Ok(store
+            .all()
+            .into_iter()
+            .filter(|r| query.matches(r))
+            .cloned()
+            .collect())
+    }
+
+    async fn sessions(&self) -> Result<Vec<SessionRow>> {
+        let mut sessions = Vec::new();
+
+        for entry in WalkDir::new(&self.data_root)
+            .min_depth(4)
+            .max_depth(4)
+            .into_iter()
+            .filter_map(Result::ok)
+        {
+            if entry.file_name() != "processing.json" {
+                continue;
+            }
+            let Some(record_dir) = entry.path().parent() else {
+                continue;
+            };
+
+            match ProcessingFile::load(record_dir).await {
+                Ok(processing) => sessions.push(processing.session_row(record_dir)),
+                Err(err) => println!("⚠ storage: skipping {} → {}", record_dir.display(), err),
+            }
+        }
+
+        sessions.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+        Ok(sessions)
+    }
+}
@@ -0,0 +1,57 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::path::Path;
+
+use super::json::JsonStorage;
+use super::sqlite::SqliteStorage;
+use super::{clear_stale, EvidenceQuery, KnowledgeQuery, Storage};
+use crate::store::settings::Settings;
+
+#[derive(Debug, Serialize)]
+pub struct MigrationSummary {
+    pub knowledge: usize,
+    pub evidence: usize,
+    pub sessions: usize,
+}
+
+/// Copies knowledge.json, the global evidence.json and every processing.json into via.db
+/// and switches the backend to SQLite. Running it again just refreshes the copy.
+pub async fn migrate_json_to_sqlite(data_root: &Path) -> Result<MigrationSummary> {
+    println!("▶ storage: migrating {} to SQLite", data_root.display());
+
+    let summary = refresh_from_json(data_root).await?;
+
+    let mut settings = Settings::load_or_create(data_root).await?;
+    settings.storage.backend = "sqlite".into();
+    settings.save(data_root).await?;
+
+    println!(
+        "▶ storage: migrated {} knowledge, {} evidence, {} sessions",
+        summary.knowledge, summary.evidence, summary.sessions
+    );
+
+    Ok(summary)
+}
+
+/// Brings via.db in line with the JSON files and clears a stale mark left by a failed mirror.
+pub(crate) async fn refresh_from_json(data_root: &Path) -> Result<MigrationSummary> {
+    let json = JsonStorage::new(data_root);
+    let db = SqliteStorage::open(data_root).await?;
+
+    let knowledge = json.knowledge(&KnowledgeQuery::default()).await?;
+    let evidence = json.evidence(&EvidenceQuery::default()).await?;
+    let sessions = json.sessions().await?;
+
+    let summary = MigrationSummary {
+        knowledge: knowledge.len(),
+        evidence: evidence.len(),
+        sessions: sessions.len(),
+    };
+
+    db.sync_knowledge(knowledge).await?;
+    db.sync_evidence(evidence).await?;
+    db.sync_sessions(sessions).await?;
+    clear_stale(data_root).await?;
+
+    Ok(summary)
+}
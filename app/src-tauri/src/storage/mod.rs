@@ -0,0 +1,133 @@
+pub mod json;
+pub mod migrate;
+pub mod sqlite;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::fs;
+
+use crate::store::evidence::EvidenceRecord;
+use crate::store::knowledge::KnowledgeRecord;
+use crate::store::lifecycle::{Approver, KnowledgeStatus};
+use crate::store::settings::Settings;
+use json::JsonStorage;
+use sqlite::SqliteStorage;
+
+/// Filter for knowledge queries, unset fields match everything.
+#[derive(Debug, Default, Clone)]
+pub struct KnowledgeQuery {
+    pub status: Option<KnowledgeStatus>,
+    pub exclude_status: Option<KnowledgeStatus>,
+    pub approved_by: Option<Approver>,
+    pub min_confidence: Option<f32>,
+}
+
+impl KnowledgeQuery {
+    pub fn matches(&self, record: &KnowledgeRecord) -> bool {
+        self.status.map_or(true, |s| record.status() == s)
+            && self.exclude_status.map_or(true, |s| record.status() != s)
+            && self.approved_by.map_or(true, |a| record.approved_by() == a)
+            && self.min_confidence.map_or(true, |c| record.confidence >= c)
+    }
+}
+
+/// Filter for evidence queries, unset fields match everything.
+#[derive(Debug, Default, Clone)]
+pub struct EvidenceQuery {
+    pub document: Option<String>,
+    pub subject_value: Option<String>,
+}
+
+impl EvidenceQuery {
+    pub fn matches(&self, record: &EvidenceRecord) -> bool {
+        self.document.as_ref().map_or(true, |d| record.documents.contains(d))
+            && self
+                .subject_value
+                .as_ref()
+                .map_or(true, |v| record.subject_value.eq_ignore_ascii_case(v))
+    }
+}
+
+/// One session as listed from processing.json.
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionRow {
+    pub doc_id: String,
+    pub record_dir: PathBuf,
+    pub source_kind: String,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    pub has_errors: bool,
+}
+
+/// Read access to knowledge, evidence and sessions.
+/// The pipeline keeps writing the JSON files; with the SQLite backend every save
+/// is mirrored into {data_root}/via.db, so commands query indexes instead of loading whole files.
+#[async_trait]
+pub trait Storage: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    async fn knowledge(&self, query: &KnowledgeQuery) -> Result<Vec<KnowledgeRecord>>;
+
+    async fn evidence(&self, query: &EvidenceQuery) -> Result<Vec<EvidenceRecord>>;
+
+    /// Newest first.
+    async fn sessions(&self) -> Result<Vec<SessionRow>>;
+}
+
+/// Left in the data root when a save could not be mirrored, via.db is behind the JSON files then.
+const STALE_MARKER: &str = "via.db.stale";
+
+/// The backend selected in settings.json (storage.backend: json | sqlite).
+/// A stale database is rebuilt from the JSON files first; if that fails, the JSON files are read.
+pub async fn open_storage(data_root: &Path) -> Result<Arc<dyn Storage>> {
+    let settings = Settings::load_or_create(data_root).await?;
+    Ok(match settings.storage.backend.as_str() {
+        "sqlite" => {
+            if data_root.join(STALE_MARKER).exists() {
+                println!("▶ storage: via.db is stale, rebuilding from JSON");
+                if let Err(err) = migrate::refresh_from_json(data_root).await {
+                    println!("⚠ storage: cannot rebuild via.db, reading JSON → {}", err);
+                    return Ok(Arc::new(JsonStorage::new(data_root)));
+                }
+            }
+            Arc::new(SqliteStorage::open(data_root).await?)
+        }
+        _ => Arc::new(JsonStorage::new(data_root)),
+    })
+}
+
+/// The SQLite database if it is the selected backend, stores mirror their saves into it.
+pub(crate) async fn mirror(data_root: &Path) -> Option<SqliteStorage> {
+    let settings = Settings::load_or_create(data_root).await.ok()?;
+    if settings.storage.backend != "sqlite" {
+        return None;
+    }
+
+    match SqliteStorage::open(data_root).await {
+        Ok(db) => Some(db),
+        Err(err) => {
+            mirror_failed(data_root, &err).await;
+            None
+        }
+    }
+}
+
+/// The JSON file is saved but via.db missed it, it is rebuilt before it is read next.
+pub(crate) async fn mirror_failed(data_root: &Path, err: &anyhow::Error) {
+    println!("⚠ storage: mirroring failed, via.db marked stale → {}", err);
+    if let Err(err) = fs::write(data_root.join(STALE_MARKER), Utc::now().to_rfc3339()).await {
+        println!("⚠ storage: cannot mark via.db stale → {}", err);
+    }
+}
+
+async fn clear_stale(data_root: &Path) -> Result<()> {
+    let marker = data_root.join(STALE_MARKER);
+    if marker.exists() {
+        fs::remove_file(&marker).await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,316 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use super::{EvidenceQuery, KnowledgeQuery, SessionRow, Storage};
+use crate::store::evidence::EvidenceRecord;
+use crate::store::knowledge::KnowledgeRecord;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS knowledge (
+    id TEXT PRIMARY KEY,
+    status TEXT NOT NULL,
+    approved_by TEXT NOT NULL,
+    confidence REAL NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS knowledge_status ON knowledge(status, approved_by);
+CREATE INDEX IF NOT EXISTS knowledge_confidence ON knowledge(confidence);
+
+CREATE TABLE IF NOT EXISTS evidence (
+    key TEXT PRIMARY KEY,
+    subject_value TEXT NOT NULL COLLATE NOCASE,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS evidence_subject ON evidence(subject_value);
+
+CREATE TABLE IF NOT EXISTS evidence_documents (
+    key TEXT NOT NULL,
+    document TEXT NOT NULL,
+    PRIMARY KEY (key, document)
+);
+CREATE INDEX IF NOT EXISTS evidence_documents_document ON evidence_documents(document);
+
+CREATE TABLE IF NOT EXISTS sessions (
+    doc_id TEXT PRIMARY KEY,
+    record_dir TEXT NOT NULL,
+    source_kind TEXT NOT NULL,
+    started_at TEXT NOT NULL,
+    finished_at TEXT,
+    has_errors INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS sessions_started_at ON sessions(started_at);
+";
+
+/// One connection per database file for the whole process, the schema is created when it is first opened.
+static CONNECTIONS: Mutex<BTreeMap<PathBuf, Arc<Mutex<Connection>>>> = Mutex::new(BTreeMap::new());
+
+/// Knowledge, evidence and sessions in {data_root}/via.db.
+/// Records are kept as JSON next to the indexed columns, so the schema only grows for new queries.
+pub struct SqliteStorage {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteStorage {
+    pub async fn open(data_root: &Path) -> Result<Self> {
+        let path = data_root.join("via.db");
+        if let Some(conn) = CONNECTIONS.lock().unwrap().get(&path) {
+            return Ok(Self { conn: conn.clone() });
+        }
+
+        let opened = path.clone();
+        let conn = tokio::task::spawn_blocking(move || -> Result<Connection> {
+            let conn = Connection::open(&opened)
+                .with_context(|| format!("failed to open {}", opened.display()))?;
+            conn.busy_timeout(Duration::from_secs(5))?;
+            conn.execute_batch(SCHEMA)
+                .context("failed to create database schema")?;
+            Ok(conn)
+        })
+        .await??;
+
+        // a concurrent open may have won, both connections are equivalent
+        let conn = CONNECTIONS
+            .lock()
+            .unwrap()
+            .entry(path)
+            .or_insert_with(|| Arc::new(Mutex::new(conn)))
+            .clone();
+        Ok(Self { conn })
+    }
+
+    /// Drops the shared connection, for when the data root was replaced and via.db is another file now.
+    pub fn close(data_root: &Path) {
+        CONNECTIONS.lock().unwrap().remove(&data_root.join("via.db"));
+    }
+
+    /// rusqlite is blocking, calls run on the blocking pool and take turns on the shared connection.
+    async fn with_connection<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> Result<T> + Send + 'static,
+    {
+        let conn = self.conn.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut conn = conn.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            f(&mut conn)
+        })
+        .await?
+    }
+
+    /// Makes the knowledge table match `records`, only new, changed and removed rows are written.
+    pub async fn sync_knowledge(&self, records: Vec<KnowledgeRecord>) -> Result<()> {
+        self.with_connection(move |conn| {
+            let tx = conn.transaction()?;
+            let stored = stored_rows(&tx, "SELECT id, data FROM knowledge")?;
+            let mut current = HashSet::new();
+            {
+                let mut upsert = tx.prepare(
+                    "INSERT OR REPLACE INTO knowledge (id, status, approved_by, confidence, data)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                )?;
+                for r in &records {
+                    current.insert(r.id.clone());
+                    let data = serde_json::to_string(r)?;
+                    if stored.get(&r.id) == Some(&data) {
+                        continue;
+                    }
+                    upsert.execute(params![
+                        r.id,
+                        r.status().as_str(),
+                        r.approved_by().as_str(),
+                        r.confidence as f64,
+                        data
+                    ])?;
+                }
+
+                let mut delete = tx.prepare("DELETE FROM knowledge WHERE id = ?1")?;
+                for id in stored.keys().filter(|id| !current.contains(*id)) {
+                    delete.execute(params![id])?;
+                }
+            }
+            tx.commit()?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Makes the evidence tables match `records`, only new, changed and removed rows are written.
+    pub async fn sync_evidence(&self, records: Vec<EvidenceRecord>) -> Result<()> {
+        self.with_connection(move |conn| {
+            let tx = conn.transaction()?;
+            let stored = stored_rows(&tx, "SELECT key, data FROM evidence")?;
+            let mut current = HashSet::new();
+            {
+                let mut upsert = tx.prepare(
+                    "INSERT OR REPLACE INTO evidence (key, subject_value, data) VALUES (?1, ?2, ?3)",
+                )?;
+                let mut delete = tx.prepare("DELETE FROM evidence WHERE key = ?1")?;
+                let mut delete_documents = tx.prepare("DELETE FROM evidence_documents WHERE key = ?1")?;
+                let mut insert_document = tx.prepare(
+                    "INSERT OR IGNORE INTO evidence_documents (key, document) VALUES (?1, ?2)",
+                )?;
+                for r in &records {
+                    current.insert(r.key.clone());
+                    let data = serde_json::to_string(r)?;
+                    if stored.get(&r.key) == Some(&data) {
+                        continue;
+                    }
+                    upsert.execute(params![r.key, r.subject_value, data])?;
+                    delete_documents.execute(params![r.key])?;
+                    for document in &r.documents {
+                        insert_document.execute(params![r.key, document])?;
+                    }
+                }
+
+                for key in stored.keys().filter(|key| !current.contains(*key)) {
+                    delete.execute(params![key])?;
+                    delete_documents.execute(params![key])?;
+                }
+            }
+            tx.commit()?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Makes the sessions table match `sessions`.
+    pub async fn sync_sessions(&self, sessions: Vec<SessionRow>) -> Result<()> {
+        self.with_connection(move |conn| {
+            let tx = conn.transaction()?;
+            tx.execute("DELETE FROM sessions", [])?;
+            for session in &sessions {
+                insert_session(&tx, session)?;
+            }
+            tx.commit()?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn upsert_session(&self, session: SessionRow) -> Result<()> {
+        self.with_connection(move |conn| insert_session(conn, &session))
+            .await
+    }
+
+    pub async fn delete_session(&self, doc_id: &str) -> Result<()> {
+        let doc_id = doc_id.to_string();
+        self.with_connection(move |conn| {
+            conn.execute("DELETE FROM sessions WHERE doc_id = ?1", params![doc_id])?;
+            Ok(())
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl Storage for SqliteStorage {
+    fn name(&self) -> &'static str {
+        "sqlite"
+    }
+
+    async fn knowledge(&self, query: &KnowledgeQuery) -> Result<Vec<KnowledgeRecord>> {
+        let mut sql = String::from("SELECT data FROM knowledge WHERE 1 = 1");
+        let mut values: Vec<Value> = Vec::new();
+        if let Some(status) = query.status {
+            sql.push_str(" AND status = ?");
+            values.push(Value::Text(status.as_str().into()));
+        }
+        if let Some(status) = query.exclude_status {
+            sql.push_str(" AND status != ?");
+            values.push(Value::Text(status.as_str().into()));
+        }
+        if let Some(approved_by) = query.approved_by {
+            sql.push_str(" AND approved_by = ?");
+            values.push(Value::Text(approved_by.as_str().into()));
+        }
+        if let Some(confidence) = query.min_confidence {
+            sql.push_str(" AND confidence >= ?");
+            values.push(Value::Real(confidence as f64));
+        }
+
+        self.with_connection(move |conn| query_json(conn, &sql, values))
+            .await
+    }
+
+    async fn evidence(&self, query: &EvidenceQuery) -> Result<Vec<EvidenceRecord>> {
+        let mut sql = String::from("SELECT data FROM evidence WHERE 1 = 1");
+        let mut values: Vec<Value> = Vec::new();
+        if let Some(document) = &query.document {
+            sql.push_str(" AND key IN (SELECT key FROM evidence_documents WHERE document = ?)");
+            values.push(Value::Text(document.clone()));
+        }
+        if let Some(subject) = &query.subject_value {
+            sql.push_str(" AND subject_value = ?");
+            values.push(Value::Text(subject.clone()));
+        }
+
+        self.with_connection(move |conn| query_json(conn, &sql, values))
+            .await
+    }
+
+    async fn sessions(&self) -> Result<Vec<SessionRow>> {
+        self.with_connection(|conn| {
+            let mut statement = conn.prepare(
+                "SELECT doc_id, record_dir, source_kind, started_at, finished_at, has_errors
+                 FROM sessions ORDER BY started_at DESC",
+            )?;
+            let rows = statement.query_map([], |row| {
+                Ok(SessionRow {
+                    doc_id: row.get(0)?,
+                    record_dir: PathBuf::from(row.get::<_, String>(1)?),
+                    source_kind: row.get(2)?,
+                    started_at: row.get(3)?,
+                    finished_at: row.get(4)?,
+                    has_errors: row.get(5)?,
+                })
+            })?;
+            Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+        })
+        .await
+    }
+}
+
+fn insert_session(conn: &Connection, session: &SessionRow) -> Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO sessions
+         (doc_id, record_dir, source_kind, started_at, finished_at, has_errors)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            session.doc_id,
+            session.record_dir.to_string_lossy(),
+            session.source_kind,
+            session.started_at,
+            session.finished_at,
+            session.has_errors
+        ],
+    )?;
+    Ok(())
+}
+
+/// Key → stored JSON, to tell which rows a sync has to write.
+fn stored_rows(conn: &Connection, sql: &str) -> Result<HashMap<String, String>> {
+    let mut statement = conn.prepare(sql)?;
+    let rows = statement.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+    Ok(rows.collect::<rusqlite::Result<HashMap<_, _>>>()?)
+}
+
+fn query_json<T: serde::de::DeserializeOwned>(
+    conn: &mut Connection,
+    sql: &str,
+    values: Vec<Value>,
+) -> Result<Vec<T>> {
+    let mut statement = conn.prepare(sql)?;
+    let rows = statement.query_map(params_from_iter(values), |row| row.get::<_, String>(0))?;
+
+    let mut records = Vec::new();
+    for data in rows {
+        records.push(serde_json::from_str(&data?).context("invalid record in database")?);
+    }
+    Ok(records)
+}
@@ -0,0 +1,69 @@
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+/// The file changed on disk since the store was loaded. Reload and apply the change again.
+#[derive(Debug)]
+pub struct StoreConflict {
+    pub path: PathBuf,
+}
+
+impl fmt::Display for StoreConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} was changed by another writer", self.path.display())
+    }
+}
+
+impl std::error::Error for StoreConflict {}
+
+/// Revision of a store file's content, compared before overwriting it.
+pub fn revision(contents: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(contents))
+}
+
+/// Fails with `StoreConflict` unless the file still has the revision it was loaded with
+/// (None: it did not exist).
+pub async fn check_revision(path: &Path, loaded: Option<&str>) -> Result<()> {
+    let current = match fs::read(path).await {
+        Ok(contents) => Some(revision(&contents)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+        Err(err) => {
+            return Err(err).with_context(|| format!("failed to read {}", path.display()));
+        }
+    };
+
+    if current.as_deref() != loaded {
+        return Err(StoreConflict {
+            path: path.to_path_buf(),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Writes next to `path` and renames over it, a crash leaves either the old or the new file.
+pub async fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+    let mut tmp_name = path
+        .file_name()
+        .context("store path has no file name")?
+        .to_os_string();
+    tmp_name.push(".tmp");
+    let tmp = path.with_file_name(tmp_name);
+
+    let mut file = fs::File::create(&tmp)
+        .await
+        .with_context(|| format!("failed to create {}", tmp.display()))?;
+    tokio::io::AsyncWriteExt::write_all(&mut file, contents)
+        .await
+        .with_context(|| format!("failed to write {}", tmp.display()))?;
+    file.sync_all()
+        .await
+        .with_context(|| format!("failed to sync {}", tmp.display()))?;
+    drop(file);
+
+    fs::rename(&tmp, path)
+        .await
+        .with_context(|| format!("failed to replace {}", path.display()))
+}
@@ -2,11 +2,31 @@ use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// One confidence for a record, from one agent run on one document.
+/// A new run of the same agent on the same document replaces its earlier contributions.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct EvidenceContribution {
+    pub document: String,
+    #[serde(default)]
+    pub agent: String,
+    #[serde(default)]
+    pub run_id: String,
+    pub confidence: f32,
+}
+
+impl EvidenceContribution {
+    fn same_source(&self, other: &Self) -> bool {
+        self.document == other.document && self.agent == other.agent && self.run_id == other.run_id
+    }
+}
+
+/// Missing fields take their defaults, so files written before a field existed keep loading.
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+#[serde(default)]
 pub struct EvidenceRecord {
     pub key: String,
 
-    pub subject_type: String,
+    pub subject_type: EntityType,
     pub subject_value: String,
     pub predicate: String,
     pub object_value: String,
@@ -15,6 +35,13 @@ pub struct EvidenceRecord {
     pub documents: Vec<String>,
     pub confidences: Vec<f32>,
     pub source_agents: Vec<String>,
+    /// Prompt templates ("name@version") that produced this evidence.
+    #[serde(default)]
+    pub prompt_versions: Vec<String>,
+    /// Where the confidences come from. Occurrences, documents, confidences and source agents
+    /// are derived from it; records written before it existed (and confirmations) have none.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub contributions: Vec<EvidenceContribution>,
 
     pub extra: serde_json::Map<String, Value>,
 
@@ -40,6 +67,8 @@ impl EvidenceRecord {
             }
         }
 
+        self.merge_prompt_versions(&incoming.prompt_versions);
+
         self.last_seen = incoming.last_seen;
     }
 }
@@ -50,7 +79,7 @@ impl EvidenceRecord {
         Self {
             key: format!(
                 "confirm:{}:{}|{}|{}",
-                k.subject_type.to_lowercase(),
+                k.subject_type.as_str(),
                 k.subject_value.to_lowercase(),
                 k.predicate,
                 k.object_value.to_lowercase()
@@ -65,6 +94,8 @@ impl EvidenceRecord {
             documents: k.source_documents.clone(),
             confidences: vec![1.0],
             source_agents: vec!["USER_CONFIRMATION".to_string()],
+            prompt_versions: Vec::new(),
+            contributions: Vec::new(),
 
             extra: {
                 let mut m = serde_json::Map::new();
@@ -88,37 +119,40 @@ impl EvidenceRecord {
 use anyhow::{Context, Result};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use tokio::fs;
+use tokio::sync::OwnedMutexGuard;
+use crate::store::atomic::{check_revision, revision, write_atomic};
 use crate::store::knowledge::KnowledgeRecord;
+use crate::store::lifecycle::EntityType;
+use crate::store::lock::lock_file;
+use crate::store::schema::{self, Loaded, EVIDENCE};
+use crate::storage;
 
 #[derive(Debug, Default)]
 pub struct EvidenceStore {
     records: HashMap<String, EvidenceRecord>,
     pub(crate) path: PathBuf,
+    /// Content revision at load, `save` refuses to overwrite a file that changed since.
+    revision: Option<String>,
+    /// Held by stores loaded with `lock_and_load`, released on drop.
+    _lock: Option<OwnedMutexGuard<()>>,
 }
 
 impl EvidenceStore {
+    /// Adds the contributions of `incoming`, a contribution already present from the same
+    /// document, agent and run is replaced.
     pub fn merge_record(&mut self, incoming: &EvidenceRecord) {
         let entry = self
             .records
             .entry(incoming.key.clone())
-            .or_insert_with(|| incoming.clone());
+            .or_insert_with(|| EvidenceRecord {
+                contributions: Vec::new(),
+                ..incoming.clone()
+            });
 
-        for doc in &incoming.documents {
-            if !entry.documents.contains(doc) {
-                entry.documents.push(doc.clone());
-                entry.occurrences += 1;
-            }
-        }
-
-        for c in &incoming.confidences {
-            entry.confidences.push(*c);
-        }
-        for agent in &incoming.source_agents {
-            if !entry.source_agents.contains(agent) {
-                entry.source_agents.push(agent.clone());
-            }
+        for c in &incoming.contributions {
+            entry.add_contribution(c.clone());
         }
+        entry.merge_prompt_versions(&incoming.prompt_versions);
 
         for (k, v) in &incoming.extra {
             entry.extra.entry(k.clone()).or_insert(v.clone());
@@ -136,21 +170,40 @@ impl EvidenceStore {
 
         if !path.exists() {
             return Ok(Self {
-                records: HashMap::new(),
                 path,
+                ..Default::default()
             });
         }
 
-        let raw = fs::read_to_string(&path)
-            .await
-            .with_context(|| format!("failed to read {}", path.display()))?;
-
-        let list: Vec<EvidenceRecord> =
-            serde_json::from_str(&raw).context("invalid evidence.json")?;
+        let Loaded { mut value, raw } = schema::load(&path, &EVIDENCE).await?;
+        let list: Vec<Value> =
+            serde_json::from_value(value["records"].take()).context("invalid evidence.json")?;
+
+        // a broken record must not hide all others, check_data reports it
+        let mut records = HashMap::new();
+        for v in list {
+            match serde_json::from_value::<EvidenceRecord>(v) {
+                Ok(r) => {
+                    records.insert(r.key.clone(), r);
+                }
+                Err(err) => println!("⚠ {}: skipping invalid record → {}", path.display(), err),
+            }
+        }
 
-        let records = list.into_iter().map(|r| (r.key.clone(), r)).collect();
+        Ok(Self {
+            records,
+            path,
+            revision: Some(revision(raw.as_bytes())),
+            _lock: None,
+        })
+    }
 
-        Ok(Self { records, path })
+    /// Loads for load-modify-save. Other writers in this process wait until the store is dropped.
+    pub async fn lock_and_load(record_dir: &Path) -> Result<Self> {
+        let guard = lock_file(&record_dir.join("evidence.json")).await;
+        let mut store = Self::load_or_create(record_dir).await?;
+        store._lock = Some(guard);
+        Ok(store)
     }
     pub fn insert_or_merge(&mut self, record: EvidenceRecord) {
         match self.records.get_mut(&record.key) {
@@ -168,26 +221,114 @@ impl EvidenceStore {
         document_id: &str,
         confidence: f32,
         agent: &str,
+        run_id: &str,
     ) {
+        let prompt_versions = record.prompt_versions.clone();
         let entry = self
             .records
             .entry(record.key.clone())
             .or_insert_with(|| record);
 
-        entry.add_occurrence(&document_id, confidence, agent);
+        entry.merge_prompt_versions(&prompt_versions);
+        entry.add_occurrence(document_id, confidence, agent, run_id);
     }
 
-    pub async fn save(&self) -> Result<()> {
+    pub async fn save(&mut self) -> Result<()> {
         let list: Vec<&EvidenceRecord> = self.records.values().collect();
 
-        let json = serde_json::to_string_pretty(&list)?;
-        fs::write(&self.path, json)
-            .await
-            .with_context(|| format!("failed to write {}", self.path.display()))?;
+        let json = schema::to_json(&EVIDENCE, &serde_json::json!({ "records": list }))?;
+        check_revision(&self.path, self.revision.as_deref()).await?;
+        write_atomic(&self.path, json.as_bytes()).await?;
+        self.revision = Some(revision(json.as_bytes()));
+
+        if let Some(data_root) = self.global_dir() {
+            if let Some(db) = storage::mirror(data_root).await {
+                if let Err(err) = db.sync_evidence(self.records.values().cloned().collect()).await {
+                    storage::mirror_failed(data_root, &err).await;
+                }
+            }
+        }
 
         Ok(())
     }
 
+    /// The data root if this is the global evidence.json, record dirs always carry a processing.json.
+    fn global_dir(&self) -> Option<&Path> {
+        self.path
+            .parent()
+            .filter(|dir| !dir.join("processing.json").exists())
+    }
+
+    /// Drops what `agent` contributed, records nothing else supports are removed entirely.
+    /// Agents call it before writing a new run, so relations they no longer find disappear.
+    pub fn remove_agent(&mut self, agent: &str) {
+        self.records.retain(|_, record| {
+            if record.contributions.is_empty() {
+                // written before contributions were tracked
+                record.source_agents.retain(|a| a != agent);
+                return !record.source_agents.is_empty();
+            }
+            record.retract(|c| c.agent == agent)
+        });
+    }
+
+    /// Merges a record of `document_id`'s evidence.json into the global store.
+    /// Records written before contributions were tracked get one per confidence.
+    pub fn merge_document(&mut self, document_id: &str, incoming: &EvidenceRecord) {
+        let mut incoming = incoming.clone();
+        if incoming.contributions.is_empty() {
+            let agent = incoming
+                .source_agents
+                .first()
+                .cloned()
+                .unwrap_or_else(|| "UNKNOWN".into());
+            incoming.contributions = incoming
+                .confidences
+                .iter()
+                .map(|c| EvidenceContribution {
+                    document: document_id.to_string(),
+                    agent: agent.clone(),
+                    run_id: "legacy".into(),
+                    confidence: *c,
+                })
+                .collect();
+        }
+        self.merge_record(&incoming);
+    }
+
+    /// Takes everything `document_id` contributed back out of the global store.
+    /// Records no document supports anymore are dropped, user confirmations stay.
+    pub fn remove_document(&mut self, document_id: &str) {
+        self.records.retain(|_, record| {
+            if !record.contributions.iter().any(|c| c.document == document_id) {
+                return true;
+            }
+            record.retract(|c| c.document == document_id)
+        });
+    }
+
+    /// For deleted documents: like `remove_document`, and user confirmations no longer list the document.
+    pub fn forget_document(&mut self, document_id: &str) {
+        self.remove_document(document_id);
+        for record in self.records.values_mut().filter(|r| r.is_confirmation()) {
+            record.documents.retain(|d| d != document_id);
+        }
+    }
+
+    /// Document evidence merged before contributions were tracked per document and agent,
+    /// deltas would miscount.
+    pub fn has_untracked_evidence(&self) -> bool {
+        self.records.values().any(|r| {
+            !r.is_confirmation()
+                && (r.contributions.is_empty() || r.contributions.iter().any(|c| c.agent.is_empty()))
+        })
+    }
+
+    /// Drops all document evidence, user confirmations stay. Start of a full rebuild.
+    pub fn retain_confirmations(&mut self) {
+        self.records.retain(|_, r| r.is_confirmation());
+    }
+
     pub fn all(&self) -> Vec<&EvidenceRecord> {
         self.records.values().collect()
     }
@@ -196,7 +337,7 @@ impl EvidenceStore {
 impl EvidenceRecord {
     pub fn new(
         key: String,
-        subject_type: String,
+        subject_type: EntityType,
         subject_value: String,
         predicate: String,
         object_value: String,
@@ -212,25 +353,71 @@ impl EvidenceRecord {
             documents: Vec::new(),
             confidences: Vec::new(),
             source_agents: Vec::new(),
+            prompt_versions: Vec::new(),
+            contributions: Vec::new(),
             extra: serde_json::Map::new(),
             first_seen: now.clone(),
             last_seen: now,
         }
     }
 
-    pub fn add_occurrence(&mut self, document_id: &str, confidence: f32, agent: &str) {
-        if !self.documents.contains(&document_id.to_string()) {
-            self.documents.push(document_id.to_string());
-            self.occurrences += 1;
+    pub fn add_occurrence(&mut self, document_id: &str, confidence: f32, agent: &str, run_id: &str) {
+        self.add_contribution(EvidenceContribution {
+            document: document_id.to_string(),
+            agent: agent.to_string(),
+            run_id: run_id.to_string(),
+            confidence,
+        });
+        self.last_seen = Utc::now().to_rfc3339();
+    }
+
+    /// Adds `contribution`, replacing one from the same document, agent and run.
+    pub fn add_contribution(&mut self, contribution: EvidenceContribution) {
+        self.contributions.retain(|c| !c.same_source(&contribution));
+        self.contributions.push(contribution);
+        self.refresh();
+    }
+
+    /// Removes the matching contributions. False if nothing supports the record anymore.
+    pub fn retract(&mut self, matches: impl Fn(&EvidenceContribution) -> bool) -> bool {
+        self.contributions.retain(|c| !matches(c));
+        if self.contributions.is_empty() {
+            return self.is_confirmation();
         }
+        self.refresh();
+        true
+    }
 
-        self.confidences.push(confidence);
+    /// Derives occurrences, documents, confidences and source agents from the contributions.
+    fn refresh(&mut self) {
+        if self.contributions.is_empty() {
+            return;
+        }
 
-        if !self.source_agents.contains(&agent.to_string()) {
-            self.source_agents.push(agent.to_string());
+        self.documents.clear();
+        self.source_agents.clear();
+        for c in &self.contributions {
+            if !self.documents.contains(&c.document) {
+                self.documents.push(c.document.clone());
+            }
+            if !self.source_agents.contains(&c.agent) {
+                self.source_agents.push(c.agent.clone());
+            }
         }
+        self.occurrences = self.documents.len();
+        self.confidences = self.contributions.iter().map(|c| c.confidence).collect();
+    }
 
-        self.last_seen = Utc::now().to_rfc3339();
+    pub fn is_confirmation(&self) -> bool {
+        self.source_agents.iter().any(|a| a == "USER_CONFIRMATION")
+    }
+
+    pub fn merge_prompt_versions(&mut self, versions: &[String]) {
+        for v in versions {
+            if !self.prompt_versions.contains(v) {
+                self.prompt_versions.push(v.clone());
+            }
+        }
     }
 
     pub fn avg_confidence(&self) -> f32 {
@@ -241,3 +428,87 @@ impl EvidenceRecord {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contribution(document: &str, agent: &str, run_id: &str, confidence: f32) -> EvidenceContribution {
+        EvidenceContribution {
+            document: document.into(),
+            agent: agent.into(),
+            run_id: run_id.into(),
+            confidence,
+        }
+    }
+
+    fn record() -> EvidenceRecord {
+        EvidenceRecord::new(
+            "person:anna|works_at|acme".into(),
+            EntityType::Person,
+            "Anna".into(),
+            "works_at".into(),
+            "ACME".into(),
+        )
+    }
+
+    #[test]
+    fn same_source_replaces_contribution() {
+        let mut r = record();
+        r.add_contribution(contribution("doc-1", "PERSON_RELATION_AGENT", "run-1", 0.4));
+        r.add_contribution(contribution("doc-1", "PERSON_RELATION_AGENT", "run-1", 0.9));
+
+        assert_eq!(r.contributions.len(), 1);
+        assert_eq!(r.confidences, vec![0.9]);
+        assert_eq!(r.occurrences, 1);
+    }
+
+    #[test]
+    fn other_sources_add_up() {
+        let mut r = record();
+        r.add_contribution(contribution("doc-1", "PERSON_RELATION_AGENT", "run-1", 0.4));
+        r.add_contribution(contribution("doc-2", "PERSON_RELATION_AGENT", "run-1", 0.8));
+        r.add_contribution(contribution("doc-2", "CONTEXT_RELATION_AGENT", "run-1", 0.6));
+
+        assert_eq!(r.contributions.len(), 3);
+        assert_eq!(r.documents, vec!["doc-1", "doc-2"]);
+        assert_eq!(r.occurrences, 2);
+        assert!((r.avg_confidence() - 0.6).abs() < 1e-6);
+    }
+
+    #[test]
+    fn retract_drops_matching_contributions() {
+        let mut r = record();
+        r.add_contribution(contribution("doc-1", "PERSON_RELATION_AGENT", "run-1", 0.4));
+        r.add_contribution(contribution("doc-2", "PERSON_RELATION_AGENT", "run-1", 0.8));
+
+        assert!(r.retract(|c| c.document == "doc-1"));
+        assert_eq!(r.documents, vec!["doc-2"]);
+        assert_eq!(r.confidences, vec![0.8]);
+
+        assert!(!r.retract(|c| c.document == "doc-2"));
+    }
+
+    #[test]
+    fn retract_keeps_confirmations() {
+        let mut r = EvidenceRecord {
+            key: "confirm:person:anna|works_at|acme".into(),
+            documents: vec!["doc-1".into()],
+            confidences: vec![1.0],
+            source_agents: vec!["USER_CONFIRMATION".into()],
+            occurrences: 1,
+            ..Default::default()
+        };
+
+        assert!(r.retract(|_| true));
+        assert_eq!(r.confidences, vec![1.0]);
+        assert_eq!(r.documents, vec!["doc-1"]);
+    }
+
+    #[test]
+    fn contributions_without_agent_and_run_load() {
+        let c: EvidenceContribution =
+            serde_json::from_str(r#"{ "document": "doc-1", "confidence": 0.5 }"#).unwrap();
+        assert_eq!(c, contribution("doc-1", "", "", 0.5));
+    }
+}
@@ -0,0 +1,90 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tokio::fs::{self, OpenOptions};
+use tokio::io::AsyncWriteExt;
+
+use crate::store::knowledge::KnowledgeRecord;
+
+const HISTORY_FILE: &str = "knowledge_history.jsonl";
+
+/// One mutation of a knowledge record. `before` is None for created records,
+/// `after` is None for removed ones.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KnowledgeChange {
+    pub change_id: String,
+    pub record_id: String,
+    pub at: String,
+    /// user, or the agent/step that made the change
+    pub actor: String,
+    pub reason: String,
+    pub before: Option<KnowledgeRecord>,
+    pub after: Option<KnowledgeRecord>,
+}
+
+/// Appends to {data_root}/knowledge_history.jsonl, one change per line. Lines are never rewritten.
+pub async fn append(data_root: &Path, changes: &[KnowledgeChange]) -> Result<()> {
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    let mut lines = String::new();
+    for change in changes {
+        lines.push_str(&serde_json::to_string(change)?);
+        lines.push('\n');
+    }
+
+    let path = data_root.join(HISTORY_FILE);
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .await
+        .with_context(|| format!("failed to open {}", path.display()))?;
+    file.write_all(lines.as_bytes())
+        .await
+        .with_context(|| format!("failed to write {}", path.display()))?;
+    file.sync_all().await?;
+
+    Ok(())
+}
+
+/// All changes, oldest first. Lines that do not parse (e.g. cut off by a crash) are skipped.
+pub async fn load(data_root: &Path) -> Result<Vec<KnowledgeChange>> {
+    let path = data_root.join(HISTORY_FILE);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let raw = fs::read_to_string(&path)
+        .await
+        .with_context(|| format!("failed to read {}", path.display()))?;
+
+    Ok(raw
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(change) => Some(change),
+            Err(err) => {
+                println!("⚠ knowledge history: skipping invalid line → {}", err);
+                None
+            }
+        })
+        .collect())
+}
+
+pub async fn record_history(data_root: &Path, record_id: &str) -> Result<Vec<KnowledgeChange>> {
+    Ok(load(data_root)
+        .await?
+        .into_iter()
+        .filter(|c| c.record_id == record_id)
+        .collect())
+}
+
+pub async fn find_change(data_root: &Path, change_id: &str) -> Result<KnowledgeChange> {
+    load(data_root)
+        .await?
+        .into_iter()
+        .find(|c| c.change_id == change_id)
+        .with_context(|| format!("change {} not found", change_id))
+}
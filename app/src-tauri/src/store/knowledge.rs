@@ -1,40 +1,52 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
-use anyhow::{Context, Result};
+use anyhow::Result;
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use tokio::fs;
-#[derive(Debug, Serialize, Deserialize, Clone)]
+use tokio::sync::OwnedMutexGuard;
+use uuid::Uuid;
+use crate::store::atomic::{check_revision, revision, write_atomic};
+use crate::store::history::{self, KnowledgeChange};
+use crate::store::lock::lock_file;
+use crate::store::schema::{self, Loaded, KNOWLEDGE};
+use crate::store::lifecycle::{Approver, EntityType, KnowledgeStatus};
+use crate::storage;
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+#[serde(default)]
 pub struct OrganizationCluster {
     pub cluster_id: String,
     pub normalized: String,
     pub variants: Vec<String>,
     pub confidence: f32,
-    pub status: String, // candidate | approved | deprecated
+    pub status: KnowledgeStatus,
     pub source_agent: String,
     pub created_at: String,
     pub updated_at: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Default, Clone)]
+#[serde(default)]
 pub struct KnowledgeClusters {
     pub organizations: Vec<OrganizationCluster>,
 }
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Missing fields take their defaults, so files written before a field existed keep loading.
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+#[serde(default)]
 pub struct KnowledgeRecord {
     pub id: String,
 
-    pub subject_type: String,
+    pub subject_type: EntityType,
     pub subject_value: String,
 
     pub predicate: String,
     pub object_value: String,
 
-    pub status: String, // candidate | approved | deprecated
+    /// Changed only through `KnowledgeStore::transition`.
+    status: KnowledgeStatus,
     pub confidence: f32,
-    pub approved_by: String, // none | user | agent
+    approved_by: Approver,
 
     pub source_documents: Vec<String>,
     pub source_agent: String,
@@ -53,8 +65,23 @@ pub struct KnowledgeStore {
     records: HashMap<String, KnowledgeRecord>,
     clusters: KnowledgeClusters,
     path: PathBuf,
+    /// Content revision at load, `save` refuses to overwrite a file that changed since.
+    revision: Option<String>,
+    /// Held by stores loaded with `lock_and_load`, released on drop.
+    _lock: Option<OwnedMutexGuard<()>>,
+    /// Records as last loaded or saved, `save` logs the difference to the change history.
+    saved: HashMap<String, KnowledgeRecord>,
+    /// Actor and reason logged for the changes of the next save.
+    actor: String,
+    reason: String,
 }
 impl KnowledgeStore {
+    /// Who makes the changes saved next and why, recorded in knowledge_history.jsonl.
+    pub fn changed_by(&mut self, actor: &str, reason: &str) {
+        self.actor = actor.into();
+        self.reason = reason.into();
+    }
+
     pub fn clusters_mut(&mut self) -> &mut KnowledgeClusters {
         &mut self.clusters
     }
@@ -70,42 +97,55 @@ impl KnowledgeStore {
 
         if !path.exists() {
             return Ok(Self {
-                records: HashMap::new(),
-                clusters: Default::default(),
                 path,
+                ..Default::default()
             });
         }
 
-        let raw = fs::read_to_string(&path)
-            .await
-            .with_context(|| format!("failed to read {}", path.display()))?;
-
-        let json: serde_json::Value =
-            serde_json::from_str(&raw).context("invalid knowledge.json")?;
+        let Loaded { value: json, raw } = schema::load(&path, &KNOWLEDGE).await?;
         let list = json
             .get("records")
             .and_then(|v| v.as_array())
             .cloned()
             .unwrap_or_default();
 
+        // a broken record must not hide all others, check_data reports it
         let mut records = HashMap::new();
         for v in list {
-            let r: KnowledgeRecord = serde_json::from_value(v)?;
-            records.insert(r.id.clone(), r);
+            match serde_json::from_value::<KnowledgeRecord>(v) {
+                Ok(r) => {
+                    records.insert(r.id.clone(), r);
+                }
+                Err(err) => println!("⚠ knowledge.json: skipping invalid record → {}", err),
+            }
         }
 
         let clusters: KnowledgeClusters = match json.get("clusters") {
-            Some(c) => serde_json::from_value(c.clone())?,
+            Some(c) => serde_json::from_value(c.clone()).unwrap_or_else(|err| {
+                println!("⚠ knowledge.json: skipping invalid clusters → {}", err);
+                KnowledgeClusters::default()
+            }),
             None => KnowledgeClusters::default(),
         };
 
         Ok(Self {
+            saved: records.clone(),
             records,
             clusters,
             path,
+            revision: Some(revision(raw.as_bytes())),
+            ..Default::default()
         })
     }
 
+    /// Loads for load-modify-save. Other writers in this process wait until the store is dropped.
+    pub async fn lock_and_load(data_root: &Path) -> Result<Self> {
+        let guard = lock_file(&data_root.join("knowledge.json")).await;
+        let mut store = Self::load_or_create(data_root).await?;
+        store._lock = Some(guard);
+        Ok(store)
+    }
+
     pub fn all(&self) -> Vec<&KnowledgeRecord> {
         self.records.values().collect()
     }
@@ -114,6 +154,66 @@ impl KnowledgeStore {
         self.records.get_mut(id)
     }
 
+    /// Moves a record through its lifecycle, see `KnowledgeStatus` for what is allowed and by whom.
+    /// Moving to the current status is a no-op.
+    pub fn transition(
+        &mut self,
+        id: &str,
+        to: KnowledgeStatus,
+        by: Approver,
+    ) -> Result<&mut KnowledgeRecord> {
+        let record = self
+            .records
+            .get_mut(id)
+            .ok_or_else(|| anyhow::anyhow!("knowledge record {} not found", id))?;
+
+        if record.status == to {
+            return Ok(record);
+        }
+        if !record.status.can_transition(to, by) {
+            anyhow::bail!(
+                "{} may not move knowledge record {} from {} to {}",
+                by,
+                id,
+                record.status,
+                to
+            );
+        }
+
+        record.status = to;
+        // a reopened record is undecided again
+        record.approved_by = if to == KnowledgeStatus::Candidate { Approver::None } else { by };
+        record.updated_at = Some(Utc::now().to_rfc3339());
+        Ok(record)
+    }
+
+    /// Removes records of `source_agent` whose id is not in `supported` anymore.
+    /// Records a user already decided on stay.
+    pub fn retract_unsupported(&mut self, source_agent: &str, supported: &HashSet<String>) -> usize {
+        let before = self.records.len();
+        self.records.retain(|id, r| {
+            r.source_agent != source_agent || r.approved_by != Approver::None || supported.contains(id)
+        });
+        before - self.records.len()
+    }
+
+    /// Drops a deleted document from every record's sources.
+    /// Returns the records that had no other source document.
+    pub fn remove_source_document(&mut self, document_id: &str) -> Vec<String> {
+        let mut unsupported = Vec::new();
+        for record in self.records.values_mut() {
+            if !record.source_documents.iter().any(|d| d == document_id) {
+                continue;
+            }
+            record.source_documents.retain(|d| d != document_id);
+            record.updated_at = Some(Utc::now().to_rfc3339());
+            if record.source_documents.is_empty() {
+                unsupported.push(record.id.clone());
+            }
+        }
+        unsupported
+    }
+
     pub fn append_or_update(&mut self, record: KnowledgeRecord) {
         match self.records.get_mut(&record.id) {
             Some(existing) => {
@@ -136,11 +236,7 @@ impl KnowledgeStore {
             }
         }
 
-        // status escalation only
-        if existing.status != "approved" {
-            existing.status = incoming.status;
-        }
-
+        // status only changes through transition()
         existing.updated_at = Some(Utc::now().to_rfc3339());
 
         // merge extra shallow
@@ -148,7 +244,56 @@ impl KnowledgeStore {
             existing.extra.entry(k).or_insert(v);
         }
     }
-    pub async fn save(&self) -> Result<()> {
+    /// Puts a record back to an earlier version, None removes it. Used by undo, restore and backup import,
+    /// which may go back past lifecycle transitions.
+    pub fn restore_version(&mut self, id: &str, version: Option<KnowledgeRecord>) {
+        match version {
+            Some(record) => {
+                self.records.insert(id.to_string(), record);
+            }
+            None => {
+                self.records.remove(id);
+            }
+        }
+    }
+
+    /// Takes over all records and clusters of `other`, e.g. a snapshot. The difference is logged on save.
+    pub fn replace_with(&mut self, other: &KnowledgeStore) {
+        self.records = other.records.clone();
+        self.clusters = other.clusters.clone();
+    }
+
+    fn pending_changes(&self) -> Vec<KnowledgeChange> {
+        let ids: HashSet<&String> = self.records.keys().chain(self.saved.keys()).collect();
+        let at = Utc::now().to_rfc3339();
+        let actor = if self.actor.is_empty() { "system" } else { self.actor.as_str() };
+
+        let mut changes: Vec<KnowledgeChange> = ids
+            .into_iter()
+            .filter_map(|id| {
+                let before = self.saved.get(id);
+                let after = self.records.get(id);
+                let unchanged = match (before, after) {
+                    (Some(b), Some(a)) => serde_json::to_value(b).ok() == serde_json::to_value(a).ok(),
+                    (None, None) => true,
+                    _ => false,
+                };
+                (!unchanged).then(|| KnowledgeChange {
+                    change_id: Uuid::new_v4().to_string(),
+                    record_id: id.clone(),
+                    at: at.clone(),
+                    actor: actor.to_string(),
+                    reason: self.reason.clone(),
+                    before: before.cloned(),
+                    after: after.cloned(),
+                })
+            })
+            .collect();
+        changes.sort_by(|a, b| a.record_id.cmp(&b.record_id));
+        changes
+    }
+
+    pub async fn save(&mut self) -> Result<()> {
         let list: Vec<&KnowledgeRecord> = self.records.values().collect();
 
         let json = serde_json::json!({
@@ -157,10 +302,21 @@ impl KnowledgeStore {
             "name_mappings": []
         });
 
-        let pretty = serde_json::to_string_pretty(&json)?;
-        fs::write(&self.path, pretty)
-            .await
-            .with_context(|| format!("failed to write {}", self.path.display()))?;
+        let pretty = schema::to_json(&KNOWLEDGE, &json)?;
+        let changes = self.pending_changes();
+        check_revision(&self.path, self.revision.as_deref()).await?;
+        write_atomic(&self.path, pretty.as_bytes()).await?;
+        self.revision = Some(revision(pretty.as_bytes()));
+
+        let data_root = self.path.parent().unwrap_or(Path::new("."));
+        history::append(data_root, &changes).await?;
+        self.saved = self.records.clone();
+
+        if let Some(db) = storage::mirror(data_root).await {
+            if let Err(err) = db.sync_knowledge(self.records.values().cloned().collect()).await {
+                storage::mirror_failed(data_root, &err).await;
+            }
+        }
 
         Ok(())
     }
@@ -169,7 +325,7 @@ impl KnowledgeStore {
 impl KnowledgeRecord {
     pub fn new(
         id: String,
-        subject_type: String,
+        subject_type: EntityType,
         subject_value: String,
         predicate: String,
         object_value: String,
@@ -184,9 +340,9 @@ impl KnowledgeRecord {
             subject_value,
             predicate,
             object_value,
-            status: "candidate".into(),
+            status: KnowledgeStatus::Candidate,
             confidence,
-            approved_by: "none".into(),
+            approved_by: Approver::None,
             source_documents,
             source_agent: source_agent.into(),
             subject_identity_id: None,
@@ -196,4 +352,12 @@ impl KnowledgeRecord {
             extra: serde_json::Map::new(),
         }
     }
+
+    pub fn status(&self) -> KnowledgeStatus {
+        self.status
+    }
+
+    pub fn approved_by(&self) -> Approver {
+        self.approved_by
+    }
 }
@@ -0,0 +1,207 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Lifecycle of a knowledge record.
+///
+/// candidate → approved    user
+/// candidate → deprecated  user, agent, system
+/// approved  → deprecated  user, system
+/// deprecated → candidate  user (reopen), system
+///
+/// system: cascades nobody decided on, deleting a session and restoring it from the trash.
+///
+/// Files store the lowercase names; unknown values read as candidate, so they show up for review again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", from = "String")]
+pub enum KnowledgeStatus {
+    #[default]
+    Candidate,
+    Approved,
+    Deprecated,
+}
+
+impl KnowledgeStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Candidate => "candidate",
+            Self::Approved => "approved",
+            Self::Deprecated => "deprecated",
+        }
+    }
+
+    pub fn can_transition(self, to: KnowledgeStatus, by: Approver) -> bool {
+        use KnowledgeStatus::*;
+        matches!(
+            (self, to, by),
+            (Candidate, Approved, Approver::User)
+                | (Candidate, Deprecated, Approver::User | Approver::Agent | Approver::System)
+                | (Approved, Deprecated, Approver::User | Approver::System)
+                | (Deprecated, Candidate, Approver::User | Approver::System)
+        )
+    }
+}
+
+impl From<String> for KnowledgeStatus {
+    fn from(value: String) -> Self {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "approved" => Self::Approved,
+            "deprecated" => Self::Deprecated,
+            _ => Self::Candidate,
+        }
+    }
+}
+
+impl fmt::Display for KnowledgeStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Who decided on a record. Unknown values read as none.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", from = "String")]
+pub enum Approver {
+    #[default]
+    None,
+    User,
+    Agent,
+    System,
+}
+
+impl Approver {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::User => "user",
+            Self::Agent => "agent",
+            Self::System => "system",
+        }
+    }
+}
+
+impl From<String> for Approver {
+    fn from(value: String) -> Self {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "user" => Self::User,
+            "agent" => Self::Agent,
+            "system" => Self::System,
+            _ => Self::None,
+        }
+    }
+}
+
+impl fmt::Display for Approver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Entity type of a subject. Types the models invent beyond the known ones are kept as they are.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum EntityType {
+    Person,
+    Organization,
+    Event,
+    Location,
+    Other(String),
+}
+
+impl Default for EntityType {
+    fn default() -> Self {
+        Self::Other(String::new())
+    }
+}
+
+impl EntityType {
+    /// Lowercase name, as used in keys and files.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Person => "person",
+            Self::Organization => "organization",
+            Self::Event => "event",
+            Self::Location => "location",
+            Self::Other(other) => other,
+        }
+    }
+}
+
+impl From<String> for EntityType {
+    fn from(value: String) -> Self {
+        let value = value.trim().to_lowercase();
+        match value.as_str() {
+            "person" => Self::Person,
+            "organization" => Self::Organization,
+            "event" => Self::Event,
+            "location" => Self::Location,
+            _ => Self::Other(value),
+        }
+    }
+}
+
+impl From<&str> for EntityType {
+    fn from(value: &str) -> Self {
+        Self::from(value.to_string())
+    }
+}
+
+impl From<EntityType> for String {
+    fn from(value: EntityType) -> Self {
+        value.as_str().to_string()
+    }
+}
+
+impl fmt::Display for EntityType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use KnowledgeStatus::*;
+
+    #[test]
+    fn agents_cannot_make_user_decisions() {
+        assert!(!Candidate.can_transition(Approved, Approver::Agent));
+        assert!(!Approved.can_transition(Deprecated, Approver::Agent));
+        assert!(!Deprecated.can_transition(Candidate, Approver::Agent));
+        assert!(Candidate.can_transition(Deprecated, Approver::Agent));
+    }
+
+    #[test]
+    fn users_decide_and_reopen() {
+        assert!(Candidate.can_transition(Approved, Approver::User));
+        assert!(Approved.can_transition(Deprecated, Approver::User));
+        assert!(Deprecated.can_transition(Candidate, Approver::User));
+        assert!(!Approved.can_transition(Candidate, Approver::User));
+        assert!(!Candidate.can_transition(Approved, Approver::None));
+    }
+
+    #[test]
+    fn the_system_never_approves() {
+        assert!(!Candidate.can_transition(Approved, Approver::System));
+        assert!(Approved.can_transition(Deprecated, Approver::System));
+        assert!(Deprecated.can_transition(Candidate, Approver::System));
+    }
+
+    #[test]
+    fn old_string_values_load() {
+        let status: KnowledgeStatus = serde_json::from_str(r#"" Approved ""#).unwrap();
+        assert_eq!(status, Approved);
+        let status: KnowledgeStatus = serde_json::from_str(r#""suggested""#).unwrap();
+        assert_eq!(status, Candidate);
+
+        let approver: Approver = serde_json::from_str(r#""USER""#).unwrap();
+        assert_eq!(approver, Approver::User);
+        let approver: Approver = serde_json::from_str(r#""""#).unwrap();
+        assert_eq!(approver, Approver::None);
+
+        let entity: EntityType = serde_json::from_str(r#""Organization""#).unwrap();
+        assert_eq!(entity, EntityType::Organization);
+        let entity: EntityType = serde_json::from_str(r#""Project""#).unwrap();
+        assert_eq!(entity, EntityType::Other("project".into()));
+        assert_eq!(serde_json::to_string(&entity).unwrap(), r#""project""#);
+    }
+}
@@ -0,0 +1,23 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
+
+static FILE_LOCKS: OnceLock<Mutex<HashMap<PathBuf, Arc<AsyncMutex<()>>>>> = OnceLock::new();
+
+/// Serializes load-modify-save of one store file inside this process.
+/// Needed since steps of one document can run concurrently and share files like evidence.json.
+pub async fn lock_file(path: &Path) -> OwnedMutexGuard<()> {
+    let lock = {
+        let mut locks = FILE_LOCKS
+            .get_or_init(|| Mutex::new(HashMap::new()))
+            .lock()
+            .unwrap();
+        locks
+            .entry(path.to_path_buf())
+            .or_insert_with(|| Arc::new(AsyncMutex::new(())))
+            .clone()
+    };
+
+    lock.lock_owned().await
+}
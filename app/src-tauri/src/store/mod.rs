@@ -1,3 +1,9 @@
 pub mod evidence;
 pub mod knowledge;
-
+pub mod settings;
+pub mod lock;
+pub mod atomic;
+pub mod schema;
+pub mod lifecycle;
+pub mod history;
+pub mod snapshot;
@@ -0,0 +1,214 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+use crate::store::atomic::write_atomic;
+
+/// Upgrades a file's content by one version.
+type Migration = fn(Value) -> Result<Value>;
+
+/// Format of one kind of file. Files carry `schema_version`; files written before that are version 0.
+/// `migrations[n]` upgrades version n to n + 1.
+pub struct Schema {
+    pub name: &'static str,
+    migrations: &'static [Migration],
+}
+
+impl Schema {
+    pub fn version(&self) -> u64 {
+        self.migrations.len() as u64
+    }
+}
+
+pub const KNOWLEDGE: Schema = Schema {
+    name: "knowledge.json",
+    migrations: &[stamp],
+};
+
+/// Global and per-document evidence.json.
+pub const EVIDENCE: Schema = Schema {
+    name: "evidence.json",
+    migrations: &[wrap_records],
+};
+
+pub const SIGNALS: Schema = Schema {
+    name: "signals.json",
+    migrations: &[wrap_records],
+};
+
+pub const PROCESSING: Schema = Schema {
+    name: "processing.json",
+    migrations: &[stamp, split_ab_tests],
+};
+
+pub const SETTINGS: Schema = Schema {
+    name: "settings.json",
+    migrations: &[stamp],
+};
+
+pub const QUEUE: Schema = Schema {
+    name: "queue.json",
+    migrations: &[stamp],
+};
+
+pub const ENTITIES: Schema = Schema {
+    name: "entities.json",
+    migrations: &[stamp],
+};
+
+pub const SNAPSHOT: Schema = Schema {
+    name: "snapshot.json",
+    migrations: &[stamp],
+};
+
+/// trash.json of a deleted session.
+pub const TRASH: Schema = Schema {
+    name: "trash.json",
+    migrations: &[stamp],
+};
+
+/// ab-{prompt}-{a}-vs-{b}.json, the comparison of a prompt A/B run.
+pub const AB_TEST: Schema = Schema {
+    name: "ab-test.json",
+    migrations: &[stamp],
+};
+
+/// manifest.json of a backup archive.
+pub const BACKUP: Schema = Schema {
+    name: "manifest.json",
+    migrations: &[stamp],
+};
+
+/// A file's content in the current version, `raw` is what is on disk now.
+pub struct Loaded {
+    pub value: Value,
+    pub raw: String,
+}
+
+/// Reads `path` and upgrades it to the current version. An upgraded file is written back,
+/// the original stays next to it as {file}.v{version}.bak.
+pub async fn load(path: &Path, schema: &Schema) -> Result<Loaded> {
+    let raw = fs::read_to_string(path)
+        .await
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    let mut value: Value =
+        serde_json::from_str(&raw).with_context(|| format!("invalid {}", schema.name))?;
+
+    let found = version_of(&value);
+    if found == schema.version() {
+        return Ok(Loaded { value, raw });
+    }
+    value = upgrade(value, schema).with_context(|| format!("cannot upgrade {}", path.display()))?;
+
+    let backup = backup_path(path, found)?;
+    if !backup.exists() {
+        fs::copy(path, &backup)
+            .await
+            .with_context(|| format!("failed to back up {}", path.display()))?;
+    }
+
+    let upgraded = serde_json::to_string_pretty(&value)?;
+    write_atomic(path, upgraded.as_bytes()).await?;
+    println!(
+        "▶ schema: upgraded {} from version {} to {}",
+        path.display(),
+        found,
+        schema.version()
+    );
+
+    Ok(Loaded {
+        value,
+        raw: upgraded,
+    })
+}
+
+/// Upgrades already parsed content to the current version in memory, nothing is written.
+pub fn upgrade(mut value: Value, schema: &Schema) -> Result<Value> {
+    let found = version_of(&value);
+    if found > schema.version() {
+        anyhow::bail!(
+            "{} has schema version {}, this app supports up to {}",
+            schema.name,
+            found,
+            schema.version()
+        );
+    }
+    for migration in &schema.migrations[found as usize..] {
+        value = migration(value)?;
+    }
+    set_version(&mut value, schema);
+    Ok(value)
+}
+
+/// Serializes `content` with the current `schema_version`. Content has to be a JSON object.
+pub fn to_json(schema: &Schema, content: &impl Serialize) -> Result<String> {
+    let mut value = serde_json::to_value(content)?;
+    anyhow::ensure!(value.is_object(), "{} content must be an object", schema.name);
+    set_version(&mut value, schema);
+    Ok(serde_json::to_string_pretty(&value)?)
+}
+
+fn version_of(value: &Value) -> u64 {
+    value
+        .get("schema_version")
+        .and_then(Value::as_u64)
+        .unwrap_or(0)
+}
+
+fn set_version(value: &mut Value, schema: &Schema) {
+    if let Some(object) = value.as_object_mut() {
+        object.insert("schema_version".into(), json!(schema.version()));
+    }
+}
+
+fn backup_path(path: &Path, version: u64) -> Result<PathBuf> {
+    let mut name = path
+        .file_name()
+        .context("file has no name")?
+        .to_os_string();
+    name.push(format!(".v{}.bak", version));
+    Ok(path.with_file_name(name))
+}
+
+/// 0 → 1: same content, only the version field is added.
+fn stamp(value: Value) -> Result<Value> {
+    anyhow::ensure!(value.is_object(), "expected a JSON object");
+    Ok(value)
+}
+
+/// 0 → 1: a bare array becomes { "records": [...] }, so the file can carry its version.
+fn wrap_records(value: Value) -> Result<Value> {
+    match value {
+        Value::Array(records) => Ok(json!({ "records": records })),
+        _ => anyhow::bail!("expected a JSON array"),
+    }
+}
+
+/// processing.json 1 → 2: A/B test runs move from `steps` ("ab-test:{prompt_id}") to `ab_tests`.
+fn split_ab_tests(mut value: Value) -> Result<Value> {
+    let object = value.as_object_mut().context("expected a JSON object")?;
+    let mut runs = Vec::new();
+    if let Some(steps) = object.get_mut("steps").and_then(Value::as_object_mut) {
+        let names: Vec<String> = steps
+            .keys()
+            .filter(|name| name.starts_with("ab-test:"))
+            .cloned()
+            .collect();
+        for name in names {
+            let Some(step) = steps.remove(&name) else {
+                continue;
+            };
+            runs.push(json!({
+                "prompt_id": name.trim_start_matches("ab-test:"),
+                "created_at": step.get("finished_at").cloned().unwrap_or(Value::Null),
+                "usage": step.pointer("/output/usage").cloned().unwrap_or_else(|| json!([])),
+                "error": (step.get("status").and_then(Value::as_str) == Some("error"))
+                    .then(|| "failed".to_string()),
+            }));
+        }
+    }
+    object.insert("ab_tests".into(), Value::Array(runs));
+    Ok(value)
+}
@@ -0,0 +1,188 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use crate::store::schema::{self, SETTINGS};
+
+/// User settings stored at {data_root}/settings.json.
+/// Every section must default, so older files keep loading.
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+pub struct Settings {
+    #[serde(default)]
+    pub budget: BudgetSettings,
+    #[serde(default)]
+    pub prompts: PromptSettings,
+    #[serde(default)]
+    pub redaction: RedactionSettings,
+    #[serde(default)]
+    pub watch_folder: WatchFolderSettings,
+    #[serde(default)]
+    pub storage: StorageSettings,
+    #[serde(default)]
+    pub trash: TrashSettings,
+    #[serde(default)]
+    pub retention: RetentionSettings,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+pub struct BudgetSettings {
+    /// Processing is refused once the estimated cost of the current month reaches this.
+    pub monthly_limit_usd: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+pub struct PromptSettings {
+    /// prompt name -> version used for processing, the built-in v1 if missing
+    #[serde(default)]
+    pub active: BTreeMap<String, String>,
+}
+
+/// What is replaced by placeholders before text is sent to an LLM.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RedactionSettings {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default = "default_true")]
+    pub emails: bool,
+    #[serde(default = "default_true")]
+    pub phone_numbers: bool,
+    #[serde(default = "default_true")]
+    pub ibans: bool,
+    #[serde(default = "default_true")]
+    pub health_terms: bool,
+    /// Person names already known in knowledge.json.
+    #[serde(default = "default_true")]
+    pub known_names: bool,
+    /// Additional names to always redact.
+    #[serde(default)]
+    pub extra_names: Vec<String>,
+    #[serde(default)]
+    pub custom_patterns: Vec<RedactionPattern>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RedactionPattern {
+    /// Placeholder label, e.g. CUSTOMER_ID → [CUSTOMER_ID_1]
+    pub label: String,
+    pub pattern: String,
+}
+
+/// Folder polled for new recordings, e.g. a synced voice-memo directory.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WatchFolderSettings {
+    /// Watching is off while unset.
+    pub path: Option<PathBuf>,
+    /// move: imported files go to {path}/imported/
+    /// mark: files stay, an empty {file}.via-imported marks them as done
+    #[serde(default = "default_after_import")]
+    pub after_import: String,
+}
+
+impl Default for WatchFolderSettings {
+    fn default() -> Self {
+        Self {
+            path: None,
+            after_import: default_after_import(),
+        }
+    }
+}
+
+/// Where commands query knowledge, evidence and sessions.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StorageSettings {
+    /// json: the JSON files, sqlite: via.db, filled by `migrate_storage`
+    #[serde(default = "default_backend")]
+    pub backend: String,
+}
+
+impl Default for StorageSettings {
+    fn default() -> Self {
+        Self {
+            backend: default_backend(),
+        }
+    }
+}
+
+/// Days after successful processing until raw sources are removed, unset keeps them.
+/// Derived knowledge is always kept.
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+pub struct RetentionSettings {
+    /// Recordings of audio sessions.
+    #[serde(default)]
+    pub audio_days: Option<u32>,
+    /// text.txt, and the stored document of text sessions.
+    #[serde(default)]
+    pub transcript_days: Option<u32>,
+}
+
+/// Deleted sessions stay restorable this long.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TrashSettings {
+    #[serde(default = "default_grace_days")]
+    pub grace_days: u32,
+}
+
+impl Default for TrashSettings {
+    fn default() -> Self {
+        Self {
+            grace_days: default_grace_days(),
+        }
+    }
+}
+
+fn default_grace_days() -> u32 {
+    30
+}
+
+fn default_backend() -> String {
+    "json".into()
+}
+
+fn default_after_import() -> String {
+    "move".into()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for RedactionSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            emails: true,
+            phone_numbers: true,
+            ibans: true,
+            health_terms: true,
+            known_names: true,
+            extra_names: Vec::new(),
+            custom_patterns: Vec::new(),
+        }
+    }
+}
+
+impl Settings {
+    pub async fn load_or_create(data_root: &Path) -> Result<Self> {
+        let path = data_root.join("settings.json");
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let loaded = schema::load(&path, &SETTINGS).await?;
+        serde_json::from_value(loaded.value).context("invalid settings.json")
+    }
+
+    pub async fn save(&self, data_root: &Path) -> Result<()> {
+        let path = data_root.join("settings.json");
+        fs::create_dir_all(data_root).await?;
+
+        let json = schema::to_json(&SETTINGS, self)?;
+        fs::write(&path, json)
+            .await
+            .with_context(|| format!("failed to write {}", path.display()))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,230 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+use crate::store::knowledge::{KnowledgeRecord, KnowledgeStore, OrganizationCluster};
+use crate::store::lock::lock_file;
+use crate::store::schema::{self, SNAPSHOT};
+
+/// manual: created by the user, never pruned
+/// auto: taken before update_global_state / rebuild_global_state / restore
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SnapshotInfo {
+    pub id: String,
+    pub created_at: String,
+    pub kind: String,
+    pub label: Option<String>,
+    pub records: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecordDiff<T> {
+    pub before: T,
+    pub after: T,
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct KnowledgeDiff {
+    pub added: Vec<KnowledgeRecord>,
+    pub removed: Vec<KnowledgeRecord>,
+    pub changed: Vec<RecordDiff<KnowledgeRecord>>,
+    pub clusters_added: Vec<OrganizationCluster>,
+    pub clusters_removed: Vec<OrganizationCluster>,
+    pub clusters_changed: Vec<RecordDiff<OrganizationCluster>>,
+}
+
+/// Snapshots live in {data_root}/snapshots/{id}/ as a copy of knowledge.json plus snapshot.json.
+/// A snapshot dir loads like a data root with `KnowledgeStore::load_or_create`.
+fn snapshots_dir(data_root: &Path) -> PathBuf {
+    data_root.join("snapshots")
+}
+
+/// Copies the current knowledge.json. Automatic snapshots of earlier days are thinned out afterwards.
+pub async fn create_snapshot(data_root: &Path, kind: &str, label: Option<String>) -> Result<SnapshotInfo> {
+    let now = Utc::now();
+    let id = format!("{}-{}", now.format("%Y%m%dT%H%M%S%.3fZ"), kind);
+    let dir = snapshots_dir(data_root).join(&id);
+    fs::create_dir_all(&dir).await?;
+
+    let source = data_root.join("knowledge.json");
+    let records = {
+        // a consistent copy, writers wait
+        let _guard = lock_file(&source).await;
+        if source.exists() {
+            fs::copy(&source, dir.join("knowledge.json"))
+                .await
+                .with_context(|| format!("failed to copy {}", source.display()))?;
+        }
+        KnowledgeStore::load_or_create(&dir).await?.all().len()
+    };
+
+    let info = SnapshotInfo {
+        id,
+        created_at: now.to_rfc3339(),
+        kind: kind.into(),
+        label,
+        records,
+    };
+    fs::write(dir.join("snapshot.json"), schema::to_json(&SNAPSHOT, &info)?)
+        .await
+        .context("failed to write snapshot.json")?;
+
+    println!("▶ snapshot {} ({} records)", info.id, info.records);
+
+    if kind == "auto" {
+        prune_automatic(data_root).await?;
+    }
+
+    Ok(info)
+}
+
+/// Oldest first.
+pub async fn list_snapshots(data_root: &Path) -> Result<Vec<SnapshotInfo>> {
+    let dir = snapshots_dir(data_root);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut snapshots = Vec::new();
+    let mut entries = fs::read_dir(&dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path().join("snapshot.json");
+        if !path.exists() {
+            continue;
+        }
+        match schema::load(&path, &SNAPSHOT)
+            .await
+            .and_then(|loaded| Ok(serde_json::from_value::<SnapshotInfo>(loaded.value)?))
+        {
+            Ok(info) => snapshots.push(info),
+            Err(err) => println!("⚠ snapshot: skipping {} → {}", path.display(), err),
+        }
+    }
+
+    snapshots.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(snapshots)
+}
+
+/// `from` and `to` are snapshot ids, "current" is the live knowledge.json.
+pub async fn diff_knowledge(data_root: &Path, from: &str, to: &str) -> Result<KnowledgeDiff> {
+    let before = load(data_root, from).await?;
+    let after = load(data_root, to).await?;
+
+    let mut diff = KnowledgeDiff::default();
+
+    let ids: HashSet<&String> = before
+        .all()
+        .into_iter()
+        .chain(after.all())
+        .map(|r| &r.id)
+        .collect();
+    for id in ids {
+        match (before.get_by_id(id), after.get_by_id(id)) {
+            (None, Some(a)) => diff.added.push(a.clone()),
+            (Some(b), None) => diff.removed.push(b.clone()),
+            (Some(b), Some(a)) if !same(b, a) => diff.changed.push(RecordDiff {
+                before: b.clone(),
+                after: a.clone(),
+            }),
+            _ => {}
+        }
+    }
+
+    let clusters_before: BTreeMap<&String, &OrganizationCluster> = before
+        .clusters()
+        .organizations
+        .iter()
+        .map(|c| (&c.cluster_id, c))
+        .collect();
+    let clusters_after: BTreeMap<&String, &OrganizationCluster> = after
+        .clusters()
+        .organizations
+        .iter()
+        .map(|c| (&c.cluster_id, c))
+        .collect();
+    for (id, b) in &clusters_before {
+        match clusters_after.get(id) {
+            None => diff.clusters_removed.push((*b).clone()),
+            // clusters are recomputed on every run, timestamps alone are no change
+            Some(a) if a.variants != b.variants || a.status != b.status => {
+                diff.clusters_changed.push(RecordDiff {
+                    before: (*b).clone(),
+                    after: (*a).clone(),
+                })
+            }
+            _ => {}
+        }
+    }
+    for (id, a) in &clusters_after {
+        if !clusters_before.contains_key(id) {
+            diff.clusters_added.push((*a).clone());
+        }
+    }
+
+    diff.added.sort_by(|a, b| a.id.cmp(&b.id));
+    diff.removed.sort_by(|a, b| a.id.cmp(&b.id));
+    diff.changed.sort_by(|a, b| a.after.id.cmp(&b.after.id));
+
+    Ok(diff)
+}
+
+/// Replaces the live knowledge with a snapshot. The current state is snapshotted first,
+/// every replaced record ends up in the change history.
+pub async fn restore_snapshot(data_root: &Path, id: &str) -> Result<()> {
+    let snapshot = load(data_root, id).await?;
+    create_snapshot(data_root, "auto", Some(format!("before restoring {}", id))).await?;
+
+    let mut knowledge = KnowledgeStore::lock_and_load(data_root).await?;
+    knowledge.replace_with(&snapshot);
+    knowledge.changed_by("user", &format!("restore snapshot {}", id));
+    knowledge.save().await?;
+
+    println!("▶ restored snapshot {}", id);
+    Ok(())
+}
+
+async fn load(data_root: &Path, id: &str) -> Result<KnowledgeStore> {
+    if id == "current" {
+        return KnowledgeStore::load_or_create(data_root).await;
+    }
+
+    let dir = snapshots_dir(data_root).join(id);
+    if id.contains(['/', '\\']) || !dir.join("snapshot.json").exists() {
+        anyhow::bail!("snapshot {} not found", id);
+    }
+    KnowledgeStore::load_or_create(&dir).await
+}
+
+fn same(a: &KnowledgeRecord, b: &KnowledgeRecord) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
+/// Keeps every automatic snapshot of the last 24 hours, and the first of each day before that.
+async fn prune_automatic(data_root: &Path) -> Result<()> {
+    let cutoff = Utc::now() - Duration::hours(24);
+    let mut kept_days = HashSet::new();
+
+    for info in list_snapshots(data_root).await? {
+        if info.kind != "auto" {
+            continue;
+        }
+        let Ok(created) = DateTime::parse_from_rfc3339(&info.created_at) else {
+            continue;
+        };
+        if created >= cutoff {
+            continue;
+        }
+        if kept_days.insert(created.date_naive()) {
+            continue;
+        }
+
+        fs::remove_dir_all(snapshots_dir(data_root).join(&info.id))
+            .await
+            .with_context(|| format!("failed to remove snapshot {}", info.id))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+/// One billable API call (chat completion or transcription).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UsageEntry {
+    pub step: String,
+    pub model: String,
+    #[serde(default)]
+    pub prompt_tokens: u64,
+    #[serde(default)]
+    pub completion_tokens: u64,
+    #[serde(default)]
+    pub audio_seconds: f64,
+    pub cost_usd: f64,
+    pub recorded_at: String,
+}
+
+impl UsageEntry {
+    pub fn chat(step: &str, model: &str, prompt_tokens: u64, completion_tokens: u64) -> Self {
+        Self {
+            step: step.into(),
+            model: model.into(),
+            prompt_tokens,
+            completion_tokens,
+            audio_seconds: 0.0,
+            cost_usd: super::pricing::chat_cost(model, prompt_tokens, completion_tokens),
+            recorded_at: Utc::now().to_rfc3339(),
+        }
+    }
+
+    pub fn transcription(step: &str, model: &str, audio_seconds: f64) -> Self {
+        Self {
+            step: step.into(),
+            model: model.into(),
+            prompt_tokens: 0,
+            completion_tokens: 0,
+            audio_seconds,
+            cost_usd: super::pricing::audio_cost(model, audio_seconds),
+            recorded_at: Utc::now().to_rfc3339(),
+        }
+    }
+
+    /// "YYYY-MM" of the call, used for monthly aggregation.
+    pub fn month(&self) -> String {
+        self.recorded_at.chars().take(7).collect()
+    }
+}
+
+/// Summed usage for one bucket of a report.
+#[derive(Debug, Serialize, Default, Clone)]
+pub struct UsageTotals {
+    pub calls: usize,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub audio_seconds: f64,
+    pub cost_usd: f64,
+}
+
+impl UsageTotals {
+    pub fn add(&mut self, entry: &UsageEntry) {
+        self.calls += 1;
+        self.prompt_tokens += entry.prompt_tokens;
+        self.completion_tokens += entry.completion_tokens;
+        self.audio_seconds += entry.audio_seconds;
+        self.cost_usd += entry.cost_usd;
+    }
+}
@@ -0,0 +1,40 @@
+// Estimated list prices in USD. These are estimates only, the invoice is the truth.
+
+/// (model prefix, input per 1M tokens, output per 1M tokens)
+/// More specific prefixes must come first.
+const CHAT_PRICES: &[(&str, f64, f64)] = &[
+    ("gpt-4.1-mini", 0.40, 1.60),
+    ("gpt-4.1-nano", 0.10, 0.40),
+    ("gpt-4.1", 2.00, 8.00),
+    ("gpt-4o-mini", 0.15, 0.60),
+    ("gpt-4o", 2.50, 10.00),
+];
+
+/// (model prefix, price per audio minute)
+const AUDIO_PRICES: &[(&str, f64)] = &[
+    ("whisper-1", 0.006),
+    ("gpt-4o-mini-transcribe", 0.003),
+    ("gpt-4o-transcribe", 0.006),
+];
+
+pub fn chat_cost(model: &str, prompt_tokens: u64, completion_tokens: u64) -> f64 {
+    match CHAT_PRICES.iter().find(|(prefix, _, _)| model.starts_with(prefix)) {
+        Some((_, input, output)) => {
+            (prompt_tokens as f64 * input + completion_tokens as f64 * output) / 1_000_000.0
+        }
+        None => {
+            println!("⚠ no price known for model {}, cost counted as 0", model);
+            0.0
+        }
+    }
+}
+
+pub fn audio_cost(model: &str, audio_seconds: f64) -> f64 {
+    match AUDIO_PRICES.iter().find(|(prefix, _)| model.starts_with(prefix)) {
+        Some((_, per_minute)) => audio_seconds / 60.0 * per_minute,
+        None => {
+            println!("⚠ no price known for model {}, cost counted as 0", model);
+            0.0
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::SystemTime;
+use tokio::fs;
+use walkdir::WalkDir;
+
+use crate::processing::processing::ProcessingFile;
+use crate::store::schema::{self, PROCESSING};
+use crate::store::settings::Settings;
+use crate::usage::model::{UsageEntry, UsageTotals};
+
+#[derive(Debug, Serialize)]
+pub struct SessionUsage {
+    pub doc_id: String,
+    pub audio_file: String,
+    pub started_at: String,
+    pub totals: UsageTotals,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BudgetStatus {
+    pub month: String,
+    pub limit_usd: f64,
+    pub spent_usd: f64,
+    pub exceeded: bool,
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct UsageReport {
+    pub total: UsageTotals,
+    pub by_session: Vec<SessionUsage>,
+    pub by_agent: BTreeMap<String, UsageTotals>,
+    pub by_month: BTreeMap<String, UsageTotals>,
+    pub budget: Option<BudgetStatus>,
+}
+
+impl UsageReport {
+    /// Aggregates the usage recorded in every processing.json below data_root.
+    pub async fn build(data_root: &Path) -> Result<Self> {
+        let mut report = UsageReport::default();
+
+        for entry in WalkDir::new(data_root)
+            .into_iter()
+            .filter_map(Result::ok)
+        {
+            if entry.file_name() != "processing.json" {
+                continue;
+            }
+            let processing = match read_processing(entry.path()).await {
+                Ok(p) => p,
+                Err(err) => {
+                    println!("⚠ skipping {} → {}", entry.path().display(), err);
+                    continue;
+                }
+            };
+
+            let mut session = UsageTotals::default();
+            for usage in usage_of(&processing) {
+                session.add(usage);
+                report.total.add(usage);
+                report.by_agent.entry(usage.step.clone()).or_default().add(usage);
+                report.by_month.entry(usage.month()).or_default().add(usage);
+            }
+
+            if session.calls > 0 {
+                report.by_session.push(SessionUsage {
+                    doc_id: processing.doc_id,
+                    audio_file: processing.audio_file,
+                    started_at: processing.started_at,
+                    totals: session,
+                });
+            }
+        }
+
+        report.by_session.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+
+        let settings = Settings::load_or_create(data_root).await?;
+        if let Some(limit_usd) = settings.budget.monthly_limit_usd {
+            let month = Utc::now().format("%Y-%m").to_string();
+            let spent_usd = report
+                .by_month
+                .get(&month)
+                .map(|t| t.cost_usd)
+                .unwrap_or(0.0);
+
+            report.budget = Some(BudgetStatus {
+                month,
+                limit_usd,
+                spent_usd,
+                exceeded: spent_usd >= limit_usd,
+            });
+        }
+
+        Ok(report)
+    }
+}
+
+/// Fails when a monthly budget is configured and already used up.
+/// Runs before every job, so only this month's cost is summed instead of building the full report.
+pub async fn ensure_within_budget(data_root: &Path) -> Result<()> {
+    let settings = Settings::load_or_create(data_root).await?;
+    let Some(limit_usd) = settings.budget.monthly_limit_usd else {
+        return Ok(());
+    };
+
+    let month = Utc::now().format("%Y-%m").to_string();
+    let spent_usd = month_cost(data_root, &month).await?;
+    if spent_usd >= limit_usd {
+        anyhow::bail!(
+            "monthly budget exceeded ({:.2} of {:.2} USD spent in {})",
+            spent_usd,
+            limit_usd,
+            month
+        );
+    }
+
+    Ok(())
+}
+
+/// Cost of the calls recorded in `month` ("YYYY-MM"). A processing.json not written since the month
+/// started cannot hold any of them and is not read.
+async fn month_cost(data_root: &Path, month: &str) -> Result<f64> {
+    let month_start: SystemTime = DateTime::parse_from_rfc3339(&format!("{}-01T00:00:00Z", month))?.into();
+
+    let mut cost = 0.0;
+    for entry in WalkDir::new(data_root)
+        .into_iter()
+        .filter_map(Result::ok)
+    {
+        if entry.file_name() != "processing.json" {
+            continue;
+        }
+        let written = entry.metadata().ok().and_then(|m| m.modified().ok());
+        if written.is_some_and(|t| t < month_start) {
+            continue;
+        }
+
+        match read_processing(entry.path()).await {
+            Ok(processing) => {
+                cost += usage_of(&processing)
+                    .filter(|usage| usage.month() == month)
+                    .map(|usage| usage.cost_usd)
+                    .sum::<f64>();
+            }
+            Err(err) => println!("⚠ skipping {} → {}", entry.path().display(), err),
+        }
+    }
+    Ok(cost)
+}
+
+/// Reads a processing.json for reporting. Older versions are upgraded in memory only,
+/// a job may be writing the file at the same time.
+async fn read_processing(path: &Path) -> Result<ProcessingFile> {
+    let value: Value = serde_json::from_str(&fs::read_to_string(path).await?)?;
+    Ok(serde_json::from_value(schema::upgrade(value, &PROCESSING)?)?)
+}
+
+/// Steps and A/B runs, both are billed.
+fn usage_of(processing: &ProcessingFile) -> impl Iterator<Item = &UsageEntry> {
+    let steps = processing
+        .steps
+        .values()
+        .filter_map(|step| step.output.as_ref())
+        .flat_map(|output| &output.usage);
+    steps.chain(processing.ab_tests.iter().flat_map(|run| &run.usage))
+}